@@ -0,0 +1,142 @@
+use crate::bignum::ops::{adc, sbb};
+use crate::bignum::BigUint;
+
+impl BigUint {
+    pub fn div_rem_u64(&self, divisor: u64) -> (BigUint, u64) {
+        assert!(divisor != 0, "division by zero");
+        let mut quotient = vec![0u64; self.limbs().len()];
+        let mut rem = 0u128;
+        for (i, limb) in self.limbs().iter().enumerate().rev() {
+            let current = (rem << 64) | *limb as u128;
+            quotient[i] = (current / divisor as u128) as u64;
+            rem = current % divisor as u128;
+        }
+        (BigUint::from_limbs(quotient), rem as u64)
+    }
+
+    // Knuth's algorithm D (TAOCP 4.3.1) - long division a limb at a time, estimating each quotient limb
+    // from the top two limbs and correcting it
+    pub fn div_rem(&self, divisor: &BigUint) -> (BigUint, BigUint) {
+        assert!(!divisor.is_zero(), "division by zero");
+        if self < divisor {
+            return (BigUint::zero(), self.clone());
+        }
+        if let [d] = divisor.limbs() {
+            let (q, r) = self.div_rem_u64(*d);
+            return (q, BigUint::from(r));
+        }
+
+        // shift so the divisor's top bit is set, which keeps the estimates within 2 of the real limb
+        let shift = divisor.limbs().last().unwrap().leading_zeros() as usize;
+        let v = (divisor << shift).limbs().to_vec();
+        let mut u = (self << shift).limbs().to_vec();
+        u.resize(self.limbs().len() + 1, 0);
+
+        let n = v.len();
+        let (v_top, v_next) = (v[n - 1] as u128, v[n - 2] as u128);
+        let mut quotient = vec![0u64; u.len() - n];
+
+        for j in (0..quotient.len()).rev() {
+            let top = ((u[j + n] as u128) << 64) | u[j + n - 1] as u128;
+            let mut q_hat = top / v_top;
+            let mut r_hat = top % v_top;
+            while q_hat > u64::MAX as u128
+                || q_hat * v_next > ((r_hat << 64) | u[j + n - 2] as u128)
+            {
+                q_hat -= 1;
+                r_hat += v_top;
+                if r_hat > u64::MAX as u128 {
+                    break;
+                }
+            }
+
+            // u[j..=j + n] -= q_hat * v
+            let (mut borrow, mut carry) = (0, 0);
+            for i in 0..n {
+                let product = q_hat * v[i] as u128 + carry as u128;
+                carry = (product >> 64) as u64;
+                let (d, b) = sbb(u[i + j], product as u64, borrow);
+                u[i + j] = d;
+                borrow = b;
+            }
+            let (d, b) = sbb(u[j + n], carry, borrow);
+            u[j + n] = d;
+
+            // rarely the estimate is still one too big and we went negative - add one v back
+            if b != 0 {
+                q_hat -= 1;
+                let mut carry = 0;
+                for i in 0..n {
+                    let (s, c) = adc(u[i + j], v[i], carry);
+                    u[i + j] = s;
+                    carry = c;
+                }
+                u[j + n] = u[j + n].wrapping_add(carry);
+            }
+            quotient[j] = q_hat as u64;
+        }
+
+        u.truncate(n);
+        (
+            BigUint::from_limbs(quotient),
+            BigUint::from_limbs(u) >> shift,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bignum::tests::big;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_div_rem() {
+        // values from python
+        let a = big("fedcba9876543210123456789abcdef0fedcba98765432100123456789abcdef");
+        let b = big("123456789abcdef0fedcba9876543");
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(q.to_hex(), "e0000000000000c6c00000000000c98e1000");
+        assert_eq!(r.to_hex(), "e4f694333333334d2d9def");
+
+        assert_eq!(b.div_rem(&a), (BigUint::zero(), b.clone()));
+        assert_eq!(a.div_rem(&a), (BigUint::one(), BigUint::zero()));
+        assert_eq!(a.div_rem_u64(10).1, 5);
+    }
+
+    #[test]
+    fn test_div_rem_random() {
+        // q * b + r == a and r < b is the whole spec
+        let mut rng = thread_rng();
+        for _i in 0..200 {
+            let a_bytes: Vec<u8> = (0..rng.gen_range(1..80)).map(|_| rng.gen()).collect();
+            let b_bytes: Vec<u8> = (0..rng.gen_range(1..40)).map(|_| rng.gen()).collect();
+            let (a, b) = (
+                BigUint::from_bytes_be(&a_bytes),
+                BigUint::from_bytes_be(&b_bytes),
+            );
+            if b.is_zero() {
+                continue;
+            }
+            let (q, r) = a.div_rem(&b);
+            assert!(r < b);
+            assert_eq!(&(&q * &b) + &r, a);
+        }
+    }
+
+    #[test]
+    fn test_div_rem_add_back() {
+        // the classic case that needs the add back step, scaled up to 64 bit limbs
+        let a = big("7fffffffffffffff800000000000000000000000000000000000000000000000");
+        let b = big("800000000000000000000000000000000000000000000001");
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(&(&q * &b) + &r, a);
+        assert!(r < b);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_div_by_zero() {
+        let _ = BigUint::one() / BigUint::zero();
+    }
+}
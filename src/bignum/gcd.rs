@@ -0,0 +1,133 @@
+use crate::bignum::BigUint;
+use std::cmp::Ordering;
+
+// Just enough of a signed integer for the Bezout coefficients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    pub negative: bool,
+    pub magnitude: BigUint,
+}
+
+impl BigInt {
+    fn new(negative: bool, magnitude: BigUint) -> Self {
+        // no negative zero
+        let negative = negative && !magnitude.is_zero();
+        BigInt {
+            negative,
+            magnitude,
+        }
+    }
+
+    fn sub(&self, other: &BigInt) -> BigInt {
+        if self.negative != other.negative {
+            return BigInt::new(self.negative, &self.magnitude + &other.magnitude);
+        }
+        match self.magnitude.cmp(&other.magnitude) {
+            Ordering::Less => BigInt::new(!self.negative, &other.magnitude - &self.magnitude),
+            _ => BigInt::new(self.negative, &self.magnitude - &other.magnitude),
+        }
+    }
+
+    fn mul(&self, other: &BigUint) -> BigInt {
+        BigInt::new(self.negative, &self.magnitude * other)
+    }
+
+    // the representative in 0..modulus
+    pub fn modulo(&self, modulus: &BigUint) -> BigUint {
+        let r = &self.magnitude % modulus;
+        if self.negative && !r.is_zero() {
+            modulus - &r
+        } else {
+            r
+        }
+    }
+}
+
+impl From<BigUint> for BigInt {
+    fn from(magnitude: BigUint) -> Self {
+        BigInt::new(false, magnitude)
+    }
+}
+
+pub fn gcd(a: &BigUint, b: &BigUint) -> BigUint {
+    let (mut a, mut b) = (a.clone(), b.clone());
+    while !b.is_zero() {
+        let r = &a % &b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+// (g, x, y) with a*x + b*y = g = gcd(a, b)
+pub fn extended_gcd(a: &BigUint, b: &BigUint) -> (BigUint, BigInt, BigInt) {
+    let (mut old_r, mut r) = (a.clone(), b.clone());
+    let (mut old_s, mut s) = (BigInt::from(BigUint::one()), BigInt::from(BigUint::zero()));
+    let (mut old_t, mut t) = (BigInt::from(BigUint::zero()), BigInt::from(BigUint::one()));
+
+    while !r.is_zero() {
+        let (q, rem) = old_r.div_rem(&r);
+        old_r = std::mem::replace(&mut r, rem);
+        let next_s = old_s.sub(&s.mul(&q));
+        old_s = std::mem::replace(&mut s, next_s);
+        let next_t = old_t.sub(&t.mul(&q));
+        old_t = std::mem::replace(&mut t, next_t);
+    }
+
+    (old_r, old_s, old_t)
+}
+
+// a^-1 mod modulus, None when they share a factor
+pub fn mod_inverse(a: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    let (g, x, _) = extended_gcd(&(a % modulus), modulus);
+    if g != BigUint::one() {
+        return None;
+    }
+    Some(x.modulo(modulus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bignum::tests::big;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(
+            gcd(&BigUint::from(240u64), &BigUint::from(46u64)),
+            BigUint::from(2u64)
+        );
+        assert_eq!(
+            gcd(&BigUint::from(7u64), &BigUint::zero()),
+            BigUint::from(7u64)
+        );
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        let (a, b) = (BigUint::from(240u64), BigUint::from(46u64));
+        let (g, x, y) = extended_gcd(&a, &b);
+        assert_eq!(g, BigUint::from(2u64));
+        // 240 * -9 + 46 * 47 = 2
+        assert_eq!(x, BigInt::new(true, BigUint::from(9u64)));
+        assert_eq!(y, BigInt::from(BigUint::from(47u64)));
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        // the cryptopals 39 example
+        assert_eq!(
+            mod_inverse(&BigUint::from(17u64), &BigUint::from(3120u64)),
+            Some(BigUint::from(2753u64))
+        );
+        assert_eq!(
+            mod_inverse(&BigUint::from(6u64), &BigUint::from(9u64)),
+            None
+        );
+
+        let m = big("fedcba9876543210fedcba9876543211fedcba9876543211");
+        let a = big("123456789abcdef0123456789");
+        let inv = mod_inverse(&a, &m).unwrap();
+        assert_eq!(&(&a * &inv) % &m, BigUint::one());
+    }
+}
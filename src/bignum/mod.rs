@@ -0,0 +1,305 @@
+mod div;
+mod gcd;
+mod montgomery;
+mod ops;
+mod prime;
+mod root;
+
+pub use gcd::{extended_gcd, gcd, mod_inverse, BigInt};
+pub use montgomery::Montgomery;
+pub use prime::{
+    generate_dsa_primes, generate_prime, generate_safe_prime, is_probable_prime, DEFAULT_ROUNDS,
+};
+
+use std::cmp::Ordering;
+use std::fmt;
+
+// Unsigned arbitrary precision integer - 64 bit limbs, least significant first, never any zero limbs on the end
+// so every number has exactly one representation and zero is no limbs at all.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        BigUint::default()
+    }
+
+    pub fn one() -> Self {
+        BigUint::from(1u64)
+    }
+
+    pub(crate) fn from_limbs(limbs: Vec<u64>) -> Self {
+        let mut n = BigUint { limbs };
+        n.normalize();
+        n
+    }
+
+    pub(crate) fn limbs(&self) -> &[u64] {
+        &self.limbs
+    }
+
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.limbs.first().is_some_and(|l| l & 1 == 1)
+    }
+
+    pub fn is_even(&self) -> bool {
+        !self.is_odd()
+    }
+
+    // bits needed to write it down, 0 for zero
+    pub fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() * 64 - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    pub fn bit(&self, i: usize) -> bool {
+        self.limbs
+            .get(i / 64)
+            .is_some_and(|l| (l >> (i % 64)) & 1 == 1)
+    }
+
+    pub fn set_bit(&mut self, i: usize) {
+        if self.limbs.len() <= i / 64 {
+            self.limbs.resize(i / 64 + 1, 0);
+        }
+        self.limbs[i / 64] |= 1 << (i % 64);
+    }
+
+    // the value if it fits in a u64
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs.len() {
+            0 => Some(0),
+            1 => Some(self.limbs[0]),
+            _ => None,
+        }
+    }
+
+    pub fn from_bytes_be(bytes: &[u8]) -> Self {
+        let limbs = bytes
+            .rchunks(8)
+            .map(|chunk| {
+                let mut limb = [0u8; 8];
+                limb[8 - chunk.len()..].copy_from_slice(chunk);
+                u64::from_be_bytes(limb)
+            })
+            .collect();
+        BigUint::from_limbs(limbs)
+    }
+
+    // big endian with no leading zeros, empty for zero
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let bytes: Vec<u8> = self
+            .limbs
+            .iter()
+            .rev()
+            .flat_map(|l| l.to_be_bytes())
+            .collect();
+        let leading = bytes.iter().take_while(|b| **b == 0).count();
+        bytes[leading..].to_vec()
+    }
+
+    // big endian, left padded with zeros to `len` bytes - None if it doesn't fit
+    pub fn to_bytes_be_padded(&self, len: usize) -> Option<Vec<u8>> {
+        let bytes = self.to_bytes_be();
+        if bytes.len() > len {
+            return None;
+        }
+        let mut padded = vec![0; len - bytes.len()];
+        padded.extend(bytes);
+        Some(padded)
+    }
+
+    // None if it isn't hex - whitespace is allowed, the big primes in the RFCs come wrapped over lines
+    pub fn from_hex(s: &str) -> Option<Self> {
+        let mut digits: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        if digits.len() % 2 == 1 {
+            digits.insert(0, '0');
+        }
+        hex::decode(digits).ok().map(|b| BigUint::from_bytes_be(&b))
+    }
+
+    pub fn to_hex(&self) -> String {
+        format!("{self:x}")
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> BigUint {
+        let mut result = BigUint::one();
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    // self^exponent mod modulus - Montgomery for odd moduli, which is all the ones crypto cares about
+    pub fn modpow(&self, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+        assert!(!modulus.is_zero(), "modpow with a zero modulus");
+        if modulus.is_odd() {
+            return Montgomery::new(modulus).modpow(self, exponent);
+        }
+
+        let mut result = BigUint::one() % modulus;
+        let base = self % modulus;
+        for i in (0..exponent.bits()).rev() {
+            result = &(&result * &result) % modulus;
+            if exponent.bit(i) {
+                result = &(&result * &base) % modulus;
+            }
+        }
+        result
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        BigUint::from_limbs(vec![n])
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::LowerHex for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.limbs.split_last() {
+            None => write!(f, "0"),
+            Some((top, rest)) => {
+                write!(f, "{top:x}")?;
+                for limb in rest.iter().rev() {
+                    write!(f, "{limb:016x}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // peel off 19 decimal digits at a time
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut chunks = Vec::new();
+        let mut n = self.clone();
+        while !n.is_zero() {
+            let (q, r) = n.div_rem_u64(CHUNK);
+            chunks.push(r);
+            n = q;
+        }
+        write!(f, "{}", chunks.last().unwrap())?;
+        for chunk in chunks.iter().rev().skip(1) {
+            write!(f, "{chunk:019}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(crate) fn big(hex: &str) -> BigUint {
+        BigUint::from_hex(hex).unwrap()
+    }
+
+    #[test]
+    fn test_hex_and_bytes() {
+        let n = big("0123456789abcdef0123456789abcdef01");
+        assert_eq!(n.to_hex(), "123456789abcdef0123456789abcdef01");
+        assert_eq!(n.bits(), 129);
+        assert_eq!(BigUint::from_bytes_be(&n.to_bytes_be()), n);
+        assert_eq!(n.to_bytes_be()[0], 0x01);
+        assert_eq!(
+            BigUint::from(0x1234u64).to_bytes_be_padded(4),
+            Some(vec![0, 0, 0x12, 0x34])
+        );
+        assert_eq!(BigUint::from(0x1234u64).to_bytes_be_padded(1), None);
+
+        assert_eq!(BigUint::zero().to_hex(), "0");
+        assert_eq!(BigUint::zero().to_bytes_be(), Vec::<u8>::new());
+        assert_eq!(BigUint::from_hex("0000"), Some(BigUint::zero()));
+        assert_eq!(BigUint::from_hex("xyz"), None);
+        assert_eq!(big("ff ff\n ff"), BigUint::from(0xffffffu64));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(
+            big("1000000000000000000000000000000").to_string(),
+            "1329227995784915872903807060280344576"
+        );
+    }
+
+    #[test]
+    fn test_bits() {
+        let mut n = BigUint::zero();
+        n.set_bit(100);
+        n.set_bit(0);
+        assert!(n.bit(100) && n.bit(0) && !n.bit(50));
+        assert_eq!(n.bits(), 101);
+        assert!(n.is_odd());
+        assert_eq!(n.to_u64(), None);
+        assert_eq!(BigUint::from(7u64).to_u64(), Some(7));
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(big("10000000000000000") > big("ffffffffffffffff"));
+        assert!(big("fffffffffffffffe") < big("ffffffffffffffff"));
+        assert!(BigUint::zero() < BigUint::one());
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(
+            BigUint::from(3u64).pow(100).to_string(),
+            "515377520732011331036461129765621272702107522001"
+        );
+        assert_eq!(BigUint::from(3u64).pow(0), BigUint::one());
+    }
+
+    #[test]
+    fn test_modpow_even_modulus() {
+        // 3^100 mod 2^64
+        let m = big("10000000000000000");
+        assert_eq!(
+            BigUint::from(3u64).modpow(&BigUint::from(100u64), &m),
+            BigUint::from(3u64.wrapping_pow(100))
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use crate::bignum::ops::{adc, mac, sub_in_place};
+use crate::bignum::BigUint;
+
+// window size for modpow - 16 precomputed powers
+const WINDOW_BITS: usize = 4;
+
+// Arithmetic mod an odd modulus m in Montgomery form, x is kept as xR mod m with R = 2^(64 * limbs).
+// Multiplying then needs no division, just a reduction by the limb.
+#[derive(Debug, Clone)]
+pub struct Montgomery {
+    modulus: BigUint,
+    m: Vec<u64>,
+    // -m^-1 mod 2^64
+    m_neg_inv: u64,
+}
+
+impl Montgomery {
+    pub fn new(modulus: &BigUint) -> Self {
+        assert!(modulus.is_odd(), "Montgomery needs an odd modulus");
+        let m = modulus.limbs().to_vec();
+
+        // Newton's iteration for the inverse mod 2^64, each step doubles the correct bits
+        let mut inv = 1u64;
+        for _i in 0..6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(m[0].wrapping_mul(inv)));
+        }
+
+        Montgomery {
+            modulus: modulus.clone(),
+            m,
+            m_neg_inv: inv.wrapping_neg(),
+        }
+    }
+
+    pub(crate) fn enter(&self, a: &BigUint) -> Vec<u64> {
+        let shifted = (a % &self.modulus) << (64 * self.m.len());
+        self.padded(&(shifted % &self.modulus))
+    }
+
+    pub(crate) fn leave(&self, a: &[u64]) -> BigUint {
+        let mut one = vec![0u64; self.m.len()];
+        one[0] = 1;
+        BigUint::from_limbs(self.mul(a, &one))
+    }
+
+    fn padded(&self, a: &BigUint) -> Vec<u64> {
+        let mut limbs = a.limbs().to_vec();
+        limbs.resize(self.m.len(), 0);
+        limbs
+    }
+
+    // abR^-1 mod m for a, b < m - the CIOS method, interleaving each row of the product with a reduction step
+    pub(crate) fn mul(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let n = self.m.len();
+        let mut t = vec![0u64; n + 2];
+
+        for b_i in b {
+            let mut carry = 0;
+            for j in 0..n {
+                (t[j], carry) = mac(t[j], a[j], *b_i, carry);
+            }
+            let (sum, c) = adc(t[n], carry, 0);
+            t[n] = sum;
+            t[n + 1] = c;
+
+            // add a multiple of m that clears the bottom limb, then shift down a limb
+            let k = t[0].wrapping_mul(self.m_neg_inv);
+            let (_, mut carry) = mac(t[0], k, self.m[0], 0);
+            for j in 1..n {
+                (t[j - 1], carry) = mac(t[j], k, self.m[j], carry);
+            }
+            let (sum, c) = adc(t[n], carry, 0);
+            t[n - 1] = sum;
+            t[n] = t[n + 1] + c;
+        }
+
+        // the result is under 2m, one subtraction at most
+        let over = t[n] != 0 || t[..n].iter().rev().cmp(self.m.iter().rev()).is_ge();
+        if over {
+            sub_in_place(&mut t[..n + 1], &self.m);
+        }
+        t.truncate(n);
+        t
+    }
+
+    pub fn modmul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let product = self.mul(&self.enter(a), &self.enter(b));
+        self.leave(&product)
+    }
+
+    pub fn modpow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        self.leave(&self.pow(&self.enter(base), exponent))
+    }
+
+    // fixed window exponentiation, from the top bit down, staying in Montgomery form throughout
+    pub(crate) fn pow(&self, base: &[u64], exponent: &BigUint) -> Vec<u64> {
+        let one = self.enter(&BigUint::one());
+
+        let mut powers = vec![one.clone(), base.to_vec()];
+        for i in 2..1 << WINDOW_BITS {
+            powers.push(self.mul(&powers[i - 1], base));
+        }
+
+        let windows = exponent.bits().div_ceil(WINDOW_BITS);
+        let mut result = one;
+        for w in (0..windows).rev() {
+            for _b in 0..WINDOW_BITS {
+                result = self.mul(&result, &result);
+            }
+            let index = (0..WINDOW_BITS)
+                .filter(|b| exponent.bit(w * WINDOW_BITS + b))
+                .fold(0, |index, b| index | 1 << b);
+            if index != 0 {
+                result = self.mul(&result, &powers[index]);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bignum::tests::big;
+    use rand::{thread_rng, Rng};
+
+    // the RFC 3526 2048 bit MODP group prime
+    const MODP_2048: &str = "
+        FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DD
+        EF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED
+        EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F
+        83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B
+        E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA0510
+        15728E5A8AACAA68FFFFFFFFFFFFFFFF";
+
+    #[test]
+    fn test_modmul() {
+        let m = big("fedcba9876543210fedcba9876543211");
+        let mont = Montgomery::new(&m);
+        let (a, b) = (
+            big("123456789abcdef0123456789"),
+            big("fedcba9876543210fedcba9876543210"),
+        );
+        assert_eq!(mont.modmul(&a, &b), &(&a * &b) % &m);
+    }
+
+    #[test]
+    fn test_modpow_small() {
+        let m = BigUint::from(1_000_000_007u64);
+        assert_eq!(
+            BigUint::from(2u64).modpow(&BigUint::from(1_000_000u64), &m),
+            BigUint::from(235042059u64)
+        );
+        assert_eq!(
+            BigUint::from(5u64).modpow(&BigUint::zero(), &m),
+            BigUint::one()
+        );
+        assert_eq!(
+            BigUint::from(5u64).modpow(&BigUint::one(), &BigUint::one()),
+            BigUint::zero()
+        );
+    }
+
+    #[test]
+    fn test_modpow_matches_square_and_multiply() {
+        let mut rng = thread_rng();
+        for _i in 0..20 {
+            let bytes = |len: usize| -> Vec<u8> { (0..len).map(|_| thread_rng().gen()).collect() };
+            let mut m = BigUint::from_bytes_be(&bytes(rng.gen_range(1..40)));
+            m.set_bit(0);
+            let base = BigUint::from_bytes_be(&bytes(50));
+            let exponent = BigUint::from_bytes_be(&bytes(rng.gen_range(0..20)));
+
+            let mut expected = BigUint::one() % &m;
+            for i in (0..exponent.bits()).rev() {
+                expected = &(&expected * &expected) % &m;
+                if exponent.bit(i) {
+                    expected = &(&expected * &base) % &m;
+                }
+            }
+            assert_eq!(base.modpow(&exponent, &m), expected);
+        }
+    }
+
+    #[test]
+    fn test_modpow_2048() {
+        let p = big(MODP_2048);
+        assert_eq!(p.bits(), 2048);
+        let p_minus_1 = &p - &BigUint::one();
+
+        // p is prime so Fermat holds
+        let base = big("123456789abcdef");
+        assert_eq!(base.modpow(&p_minus_1, &p), BigUint::one());
+        // and 2 generates the subgroup of order q = (p - 1) / 2
+        let q = &p_minus_1 >> 1;
+        assert_eq!(BigUint::from(2u64).modpow(&q, &p), BigUint::one());
+    }
+}
@@ -0,0 +1,252 @@
+use crate::bignum::BigUint;
+use std::ops::{Add, Div, Mul, Rem, Shl, Shr, Sub};
+
+// a + b + carry, returning the low limb and the new carry
+#[inline]
+pub(crate) fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let sum = a as u128 + b as u128 + carry as u128;
+    (sum as u64, (sum >> 64) as u64)
+}
+
+// a - b - borrow, returning the low limb and the new borrow (0 or 1)
+#[inline]
+pub(crate) fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let diff = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (diff as u64, (diff >> 127) as u64)
+}
+
+// a + b * c + carry, which always fits in 128 bits
+#[inline]
+pub(crate) fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let sum = a as u128 + b as u128 * c as u128 + carry as u128;
+    (sum as u64, (sum >> 64) as u64)
+}
+
+// a -= b in place, returns the borrow out of the top - a has to be at least as long as b
+pub(crate) fn sub_in_place(a: &mut [u64], b: &[u64]) -> u64 {
+    let mut borrow = 0;
+    for (i, a) in a.iter_mut().enumerate() {
+        let (d, br) = sbb(*a, b.get(i).cloned().unwrap_or(0), borrow);
+        *a = d;
+        borrow = br;
+    }
+    borrow
+}
+
+impl BigUint {
+    pub fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        if self < other {
+            return None;
+        }
+        let mut limbs = self.limbs().to_vec();
+        sub_in_place(&mut limbs, other.limbs());
+        Some(BigUint::from_limbs(limbs))
+    }
+
+    fn add_ref(&self, other: &BigUint) -> BigUint {
+        let (long, short) = if self.limbs().len() >= other.limbs().len() {
+            (self.limbs(), other.limbs())
+        } else {
+            (other.limbs(), self.limbs())
+        };
+
+        let mut limbs = Vec::with_capacity(long.len() + 1);
+        let mut carry = 0;
+        for (i, l) in long.iter().enumerate() {
+            let (sum, c) = adc(*l, short.get(i).cloned().unwrap_or(0), carry);
+            limbs.push(sum);
+            carry = c;
+        }
+        limbs.push(carry);
+        BigUint::from_limbs(limbs)
+    }
+
+    fn mul_ref(&self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+        let (a, b) = (self.limbs(), other.limbs());
+        let mut limbs = vec![0u64; a.len() + b.len()];
+        for (i, x) in a.iter().enumerate() {
+            let mut carry = 0;
+            for (j, y) in b.iter().enumerate() {
+                let (low, c) = mac(limbs[i + j], *x, *y, carry);
+                limbs[i + j] = low;
+                carry = c;
+            }
+            limbs[i + b.len()] = carry;
+        }
+        BigUint::from_limbs(limbs)
+    }
+
+    fn shl_ref(&self, shift: usize) -> BigUint {
+        if self.is_zero() {
+            return BigUint::zero();
+        }
+        let (whole, bits) = (shift / 64, shift % 64);
+        let mut limbs = vec![0u64; whole];
+        if bits == 0 {
+            limbs.extend_from_slice(self.limbs());
+        } else {
+            let mut carry = 0;
+            for l in self.limbs() {
+                limbs.push((l << bits) | carry);
+                carry = l >> (64 - bits);
+            }
+            limbs.push(carry);
+        }
+        BigUint::from_limbs(limbs)
+    }
+
+    fn shr_ref(&self, shift: usize) -> BigUint {
+        let (whole, bits) = (shift / 64, shift % 64);
+        if whole >= self.limbs().len() {
+            return BigUint::zero();
+        }
+        let rest = &self.limbs()[whole..];
+        let limbs = if bits == 0 {
+            rest.to_vec()
+        } else {
+            rest.iter()
+                .enumerate()
+                .map(|(i, l)| {
+                    let high = rest.get(i + 1).map_or(0, |h| h << (64 - bits));
+                    (l >> bits) | high
+                })
+                .collect()
+        };
+        BigUint::from_limbs(limbs)
+    }
+}
+
+// each operator for every mix of owned and borrowed operands, all going through the borrowed version
+macro_rules! forward_binop {
+    ($trait:ident, $method:ident, $imp:ident) => {
+        impl $trait<&BigUint> for &BigUint {
+            type Output = BigUint;
+            fn $method(self, other: &BigUint) -> BigUint {
+                self.$imp(other)
+            }
+        }
+
+        impl $trait<BigUint> for BigUint {
+            type Output = BigUint;
+            fn $method(self, other: BigUint) -> BigUint {
+                (&self).$imp(&other)
+            }
+        }
+
+        impl $trait<&BigUint> for BigUint {
+            type Output = BigUint;
+            fn $method(self, other: &BigUint) -> BigUint {
+                (&self).$imp(other)
+            }
+        }
+
+        impl $trait<BigUint> for &BigUint {
+            type Output = BigUint;
+            fn $method(self, other: BigUint) -> BigUint {
+                self.$imp(&other)
+            }
+        }
+    };
+}
+
+impl BigUint {
+    fn sub_ref(&self, other: &BigUint) -> BigUint {
+        self.checked_sub(other)
+            .expect("attempt to subtract a bigger BigUint")
+    }
+
+    fn div_ref(&self, other: &BigUint) -> BigUint {
+        self.div_rem(other).0
+    }
+
+    fn rem_ref(&self, other: &BigUint) -> BigUint {
+        self.div_rem(other).1
+    }
+}
+
+forward_binop!(Add, add, add_ref);
+forward_binop!(Sub, sub, sub_ref);
+forward_binop!(Mul, mul, mul_ref);
+forward_binop!(Div, div, div_ref);
+forward_binop!(Rem, rem, rem_ref);
+
+impl Shl<usize> for &BigUint {
+    type Output = BigUint;
+    fn shl(self, shift: usize) -> BigUint {
+        self.shl_ref(shift)
+    }
+}
+
+impl Shl<usize> for BigUint {
+    type Output = BigUint;
+    fn shl(self, shift: usize) -> BigUint {
+        self.shl_ref(shift)
+    }
+}
+
+impl Shr<usize> for &BigUint {
+    type Output = BigUint;
+    fn shr(self, shift: usize) -> BigUint {
+        self.shr_ref(shift)
+    }
+}
+
+impl Shr<usize> for BigUint {
+    type Output = BigUint;
+    fn shr(self, shift: usize) -> BigUint {
+        self.shr_ref(shift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bignum::tests::big;
+
+    #[test]
+    fn test_add_sub() {
+        let a = big("ffffffffffffffffffffffffffffffff");
+        let b = BigUint::one();
+        assert_eq!(&a + &b, big("100000000000000000000000000000000"));
+        assert_eq!(&(&a + &b) - &b, a);
+        assert_eq!(&a - &a, BigUint::zero());
+        assert_eq!(b.checked_sub(&a), None);
+        assert_eq!(BigUint::zero() + BigUint::zero(), BigUint::zero());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sub_underflow() {
+        let _ = BigUint::one() - BigUint::from(2u64);
+    }
+
+    #[test]
+    fn test_mul() {
+        // values from python
+        let a = big("123456789abcdef0fedcba9876543210");
+        let b = big("fedcba98765432100123456789abcdef");
+        assert_eq!(
+            (&a * &b).to_hex(),
+            "121fa00ad77d7423213d0003e234949aaa6c876160ec6a522236d88fe5618cf0"
+        );
+        assert_eq!(&a * &BigUint::zero(), BigUint::zero());
+        assert_eq!(&a * &BigUint::one(), a);
+    }
+
+    #[test]
+    fn test_shifts() {
+        let a = big("123456789abcdef0fedcba9876543210");
+        assert_eq!((&a << 4).to_hex(), "123456789abcdef0fedcba98765432100");
+        assert_eq!(
+            (&a << 64).to_hex(),
+            "123456789abcdef0fedcba98765432100000000000000000"
+        );
+        assert_eq!((&a >> 4).to_hex(), "123456789abcdef0fedcba987654321");
+        assert_eq!((&a >> 68).to_hex(), "123456789abcdef");
+        assert_eq!(&a >> 200, BigUint::zero());
+        assert_eq!(&(&a << 77) >> 77, a);
+    }
+}
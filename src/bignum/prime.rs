@@ -0,0 +1,274 @@
+use crate::bignum::{BigUint, Montgomery};
+use rand::Rng;
+use std::sync::OnceLock;
+
+// Miller-Rabin rounds with random bases for numbers too big for the deterministic bases, error under 4^-40
+pub const DEFAULT_ROUNDS: usize = 40;
+
+const SMALL_PRIME_LIMIT: u64 = 2000;
+
+// the first 13 primes as bases are enough to decide any n below this (Sorenson and Webster 2015)
+const DETERMINISTIC_BASES: [u64; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+const DETERMINISTIC_LIMIT: &str = "2be6951adc5b22410a5fd";
+
+fn small_primes() -> &'static [u64] {
+    static PRIMES: OnceLock<Vec<u64>> = OnceLock::new();
+    PRIMES.get_or_init(|| {
+        let mut sieve = vec![true; SMALL_PRIME_LIMIT as usize];
+        (2..SMALL_PRIME_LIMIT)
+            .filter(|&i| {
+                if !sieve[i as usize] {
+                    return false;
+                }
+                for multiple in (i * i..SMALL_PRIME_LIMIT).step_by(i as usize) {
+                    sieve[multiple as usize] = false;
+                }
+                true
+            })
+            .collect()
+    })
+}
+
+impl BigUint {
+    // uniform in 0..bound
+    pub fn random_below<R: Rng + ?Sized>(bound: &BigUint, rng: &mut R) -> BigUint {
+        assert!(!bound.is_zero(), "nothing is below zero");
+        let bits = bound.bits();
+        // rejection sampling, each try is under the bound at least half the time
+        loop {
+            let candidate = BigUint::random_bits(bits, rng);
+            if candidate < *bound {
+                return candidate;
+            }
+        }
+    }
+
+    // uniform with at most `bits` bits
+    pub fn random_bits<R: Rng + ?Sized>(bits: usize, rng: &mut R) -> BigUint {
+        let mut bytes = vec![0u8; bits.div_ceil(8)];
+        rng.fill(&mut bytes[..]);
+        if !bits.is_multiple_of(8) {
+            bytes[0] &= 0xff >> (8 - bits % 8);
+        }
+        BigUint::from_bytes_be(&bytes)
+    }
+}
+
+// Some(answer) if trial division settles it
+fn trial_division(n: &BigUint) -> Option<bool> {
+    if let Some(small) = n.to_u64() {
+        if small < 2 {
+            return Some(false);
+        }
+        if small < SMALL_PRIME_LIMIT {
+            return Some(small_primes().binary_search(&small).is_ok());
+        }
+    }
+    if small_primes().iter().any(|p| n.div_rem_u64(*p).1 == 0) {
+        return Some(false);
+    }
+    None
+}
+
+// The numbers a round needs, all in Montgomery form mod n so a round never leaves it
+struct MillerRabin {
+    mont: Montgomery,
+    one: Vec<u64>,
+    minus_one: Vec<u64>,
+    // n - 1 = d * 2^s with d odd
+    d: BigUint,
+    s: usize,
+}
+
+impl MillerRabin {
+    fn new(n: &BigUint) -> Self {
+        let n_minus_1 = n - &BigUint::one();
+        let s = (0..).find(|i| n_minus_1.bit(*i)).unwrap();
+        let mont = Montgomery::new(n);
+
+        MillerRabin {
+            one: mont.enter(&BigUint::one()),
+            minus_one: mont.enter(&n_minus_1),
+            d: &n_minus_1 >> s,
+            s,
+            mont,
+        }
+    }
+
+    // one round of Miller-Rabin, false means `a` witnesses that n is composite
+    fn round(&self, a: &BigUint) -> bool {
+        let mut x = self.mont.pow(&self.mont.enter(a), &self.d);
+        if x == self.one || x == self.minus_one {
+            return true;
+        }
+        for _r in 1..self.s {
+            x = self.mont.mul(&x, &x);
+            if x == self.minus_one {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// Miller-Rabin after trial division - exact below about 2^81 using fixed bases, otherwise `rounds` random bases.
+pub fn is_probable_prime<R: Rng + ?Sized>(n: &BigUint, rounds: usize, rng: &mut R) -> bool {
+    if let Some(answer) = trial_division(n) {
+        return answer;
+    }
+
+    let miller_rabin = MillerRabin::new(n);
+
+    if *n < BigUint::from_hex(DETERMINISTIC_LIMIT).unwrap() {
+        return DETERMINISTIC_BASES
+            .iter()
+            .all(|a| miller_rabin.round(&BigUint::from(*a)));
+    }
+
+    // bases in 2..n - 1
+    let range = n - &BigUint::from(3u64);
+    (0..rounds).all(|_| {
+        let a = &BigUint::random_below(&range, rng) + &BigUint::from(2u64);
+        miller_rabin.round(&a)
+    })
+}
+
+// A random prime of exactly `bits` bits. The top two bits are set so two of them multiply to exactly 2 * bits, as RSA wants.
+pub fn generate_prime<R: Rng + ?Sized>(bits: usize, rng: &mut R) -> BigUint {
+    assert!(
+        bits >= 3,
+        "no primes with the top two bits set under 3 bits"
+    );
+    loop {
+        let mut candidate = BigUint::random_bits(bits, rng);
+        candidate.set_bit(bits - 1);
+        candidate.set_bit(bits - 2);
+        candidate.set_bit(0);
+        if is_probable_prime(&candidate, DEFAULT_ROUNDS, rng) {
+            return candidate;
+        }
+    }
+}
+
+// A prime p = 2q + 1 with q prime too, of exactly `bits` bits - the Diffie-Hellman kind where the group has no small subgroups.
+pub fn generate_safe_prime<R: Rng + ?Sized>(bits: usize, rng: &mut R) -> BigUint {
+    assert!(bits >= 3, "the smallest safe prime is 5");
+    loop {
+        let mut q = BigUint::random_bits(bits - 1, rng);
+        q.set_bit(bits - 2);
+        q.set_bit(0);
+        let p = &(&q << 1) + &BigUint::one();
+
+        // both have to get through trial division before either is worth Miller-Rabin
+        if trial_division(&q) == Some(false) || trial_division(&p) == Some(false) {
+            continue;
+        }
+        if is_probable_prime(&q, DEFAULT_ROUNDS, rng) && is_probable_prime(&p, DEFAULT_ROUNDS, rng)
+        {
+            return p;
+        }
+    }
+}
+
+// DSA domain parameters: a `q_bits` prime q and an `p_bits` prime p with q | p - 1, following the shape
+// of FIPS 186-4 A.1.1.2 but with plain random numbers instead of the seeded hash construction.
+// Like the standard a q gets 4 * p_bits tries at finding a p before it's thrown away - some q have no p
+// at all, e.g. when p_bits is q_bits + 1 the only candidate is 2q + 1.
+pub fn generate_dsa_primes<R: Rng + ?Sized>(
+    p_bits: usize,
+    q_bits: usize,
+    rng: &mut R,
+) -> (BigUint, BigUint) {
+    assert!(p_bits > q_bits, "p has to be bigger than q");
+    loop {
+        let q = generate_prime(q_bits, rng);
+        let two_q = &q << 1;
+
+        // X random with the top bit set, then p = X - (X mod 2q) + 1 is 1 mod 2q
+        for _attempt in 0..4 * p_bits {
+            let mut x = BigUint::random_bits(p_bits, rng);
+            x.set_bit(p_bits - 1);
+            let p = &(&x - &(&x % &two_q)) + &BigUint::one();
+            if p.bits() == p_bits && is_probable_prime(&p, DEFAULT_ROUNDS, rng) {
+                return (p, q);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bignum::tests::big;
+    use rand::thread_rng;
+
+    fn is_prime(n: &BigUint) -> bool {
+        is_probable_prime(n, DEFAULT_ROUNDS, &mut thread_rng())
+    }
+
+    #[test]
+    fn test_small_primes() {
+        let primes: Vec<u64> = (0..100u64)
+            .filter(|n| is_prime(&BigUint::from(*n)))
+            .collect();
+        assert_eq!(
+            primes,
+            vec![
+                2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79,
+                83, 89, 97
+            ]
+        );
+        assert_eq!(small_primes().len(), 303);
+    }
+
+    #[test]
+    fn test_is_probable_prime() {
+        // 2^61 - 1 and 2^127 - 1 are Mersenne primes, 2^67 - 1 isn't
+        assert!(is_prime(&big("1fffffffffffffff")));
+        assert!(is_prime(&big("7fffffffffffffffffffffffffffffff")));
+        assert!(!is_prime(&big("7ffffffffffffffff")));
+
+        // Carmichael numbers fool Fermat but not Miller-Rabin
+        for n in [561u64, 41041, 825265, 321197185] {
+            assert!(!is_prime(&BigUint::from(n)));
+        }
+        // the smallest strong pseudoprime to every base up to 37 - it takes base 41 to catch it
+        assert!(!is_prime(&big("437ae92817f9fc85b7e5")));
+        // a product of two primes bigger than the trial division limit
+        assert!(!is_prime(
+            &(&big("1fffffffffffffff") * &big("7fffffffffffffffffffffffffffffff"))
+        ));
+    }
+
+    #[test]
+    fn test_generate_prime() {
+        let mut rng = thread_rng();
+        let p = generate_prime(256, &mut rng);
+        assert_eq!(p.bits(), 256);
+        assert!(p.bit(254));
+        assert!(is_prime(&p));
+    }
+
+    #[test]
+    fn test_generate_safe_prime() {
+        let mut rng = thread_rng();
+        let p = generate_safe_prime(128, &mut rng);
+        assert_eq!(p.bits(), 128);
+        assert!(is_prime(&p));
+        assert!(is_prime(&(&p >> 1)));
+    }
+
+    #[test]
+    fn test_generate_dsa_primes() {
+        let mut rng = thread_rng();
+        let (p, q) = generate_dsa_primes(512, 160, &mut rng);
+        assert_eq!((p.bits(), q.bits()), (512, 160));
+        assert!(is_prime(&p) && is_prime(&q));
+        assert_eq!(&(&p - &BigUint::one()) % &q, BigUint::zero());
+
+        // one bit apart leaves p = 2q + 1 as the only choice, so most q have to be replaced
+        let (p, q) = generate_dsa_primes(65, 64, &mut rng);
+        assert_eq!(p, &(&q << 1) + &BigUint::one());
+        assert!(is_prime(&p) && is_prime(&q));
+    }
+}
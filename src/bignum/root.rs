@@ -0,0 +1,47 @@
+use crate::bignum::BigUint;
+
+impl BigUint {
+    // floor of the nth root, by Newton's method from an overestimate so it comes down monotonically
+    pub fn nth_root(&self, n: u32) -> BigUint {
+        assert!(n > 0, "0th root");
+        if self.is_zero() || n == 1 {
+            return self.clone();
+        }
+
+        let n_big = BigUint::from(n as u64);
+        let n_minus_1 = BigUint::from(n as u64 - 1);
+
+        // 2^ceil(bits / n) is at least the root
+        let mut x = BigUint::zero();
+        x.set_bit(self.bits().div_ceil(n as usize));
+        loop {
+            let next = &(&(&n_minus_1 * &x) + &(self / &x.pow(n - 1))) / &n_big;
+            if next >= x {
+                return x;
+            }
+            x = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bignum::tests::big;
+
+    #[test]
+    fn test_nth_root() {
+        assert_eq!(BigUint::from(27u64).nth_root(3), BigUint::from(3u64));
+        assert_eq!(BigUint::from(26u64).nth_root(3), BigUint::from(2u64));
+        assert_eq!(BigUint::from(1u64).nth_root(5), BigUint::one());
+        assert_eq!(BigUint::zero().nth_root(3), BigUint::zero());
+        assert_eq!(BigUint::from(99u64).nth_root(1), BigUint::from(99u64));
+
+        // a cube that's well past a few limbs - the e = 3 RSA broadcast case
+        let m = big("123456789abcdef0fedcba9876543210123456789abcdef");
+        let cube = m.pow(3);
+        assert_eq!(cube.nth_root(3), m);
+        assert_eq!((&cube - &BigUint::one()).nth_root(3), &m - &BigUint::one());
+        assert_eq!((&cube + &BigUint::one()).nth_root(3), m);
+    }
+}
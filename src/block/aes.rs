@@ -1,112 +1,72 @@
-use openssl::symm::{Cipher, Crypter, Mode};
+use crate::block::ctr::{CounterLayout, Ctr};
+use crate::block::modes::{cbc_decrypt, cbc_encrypt, ecb_decrypt, ecb_encrypt};
+use crate::block::padding::Pkcs7;
+use crate::error::CryptoError;
 
-use crate::block::padding::strip_padding;
-use openssl::symm::Mode::{Decrypt, Encrypt};
+#[cfg(not(feature = "soft-aes"))]
+use crate::block::openssl_aes::OpensslAes;
+#[cfg(feature = "soft-aes")]
+use crate::block::soft_aes::SoftAes;
 
-const BLOCK_SIZE: usize = 16;
+#[cfg(not(any(feature = "openssl", feature = "soft-aes")))]
+compile_error!("enable the `openssl` or `soft-aes` feature to pick an AES backend");
 
-pub fn aes_128_ecb_decrypt(key: &[u8], input: &[u8]) -> Vec<u8> {
-    let mut output = Vec::with_capacity(input.len());
-    let num_blocks = input.len() / BLOCK_SIZE;
+pub const BLOCK_SIZE: usize = 16;
 
-    for i in 0..num_blocks {
-        let mut block_out =
-            aes_128_ecb_block(key, &input[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE], Decrypt);
-        output.append(&mut block_out);
-    }
-
-    strip_padding(&mut output, BLOCK_SIZE);
-    output
-}
+// the pure rust backend wins if both are enabled
+#[cfg(feature = "soft-aes")]
+pub type Aes<const KEY_SIZE: usize> = SoftAes<KEY_SIZE>;
+#[cfg(not(feature = "soft-aes"))]
+pub type Aes<const KEY_SIZE: usize> = OpensslAes<KEY_SIZE>;
 
-pub fn aes_128_ecb_encrypt(key: &[u8], mut input: Vec<u8>) -> Vec<u8> {
-    let padding_bytes = crate::block::padding::pad(&mut input, BLOCK_SIZE);
-    let mut output = Vec::with_capacity(input.len() + padding_bytes);
+pub type Aes128 = Aes<16>;
+pub type Aes192 = Aes<24>;
+pub type Aes256 = Aes<32>;
 
-    let num_blocks = input.len() / 16;
-    for i in 0..num_blocks {
-        let mut block_out =
-            aes_128_ecb_block(key, &input[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE], Encrypt);
-        output.append(&mut block_out);
-    }
-    output
+pub fn aes_128_ecb_decrypt(key: &[u8], input: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    ecb_decrypt(&Aes128::new(key)?, &Pkcs7, input)
 }
 
-fn aes_128_ecb_block(key: &[u8], input: &[u8], mode: Mode) -> Vec<u8> {
-    assert_eq!(input.len(), BLOCK_SIZE);
-
-    let t = Cipher::aes_128_ecb();
-    let mut c = Crypter::new(t, mode, key, None).unwrap();
-    c.pad(false);
-
-    let mut out = vec![0; input.len() + t.block_size()];
-    let count = c.update(input, &mut out).unwrap();
-    let rest = c.finalize(&mut out[count..]).unwrap();
-    out.truncate(count + rest);
-    out
+pub fn aes_128_ecb_encrypt(key: &[u8], input: Vec<u8>) -> Result<Vec<u8>, CryptoError> {
+    ecb_encrypt(&Aes128::new(key)?, &Pkcs7, input)
 }
 
-fn xor_in_place(data: &mut [u8], key: &[u8]) {
-    data.iter_mut().zip(key).for_each(|(b, k)| *b ^= k);
+pub fn aes_128_cbc_decrypt(key: &[u8], input: Vec<u8>, iv: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    cbc_decrypt(&Aes128::new(key)?, &Pkcs7, input, iv)
 }
 
-pub fn aes_128_cbc_decrypt(key: &[u8], input: Vec<u8>, iv: &[u8]) -> Vec<u8> {
-    let mut result = Vec::new();
-
-    let num_blocks = input.len() / 16;
-
-    // block 0 decrypt then xor with iv
-    // block 1 decrpyt then xor with block 0 (cipher)
-
-    for i in 0..num_blocks {
-        let mut plain = aes_128_ecb_block(
-            key,
-            &input[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE],
-            Mode::Decrypt,
-        );
-
-        let chain = if i == 0 {
-            iv
-        } else {
-            &input[(i - 1) * BLOCK_SIZE..i * BLOCK_SIZE]
-        };
-
-        xor_in_place(&mut plain[..], chain);
-
-        result.append(&mut plain);
-    }
-
-    strip_padding(&mut result, BLOCK_SIZE);
-    result
+pub fn aes_128_cbc_encrypt(key: &[u8], input: Vec<u8>, iv: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    cbc_encrypt(&Aes128::new(key)?, &Pkcs7, input, iv)
 }
 
-pub fn aes_128_cbc_encrypt(key: &[u8], mut input: Vec<u8>, iv: &[u8]) -> Vec<u8> {
-    let mut result = Vec::new();
-
-    let _padding_bytes = crate::block::padding::pad(&mut input, BLOCK_SIZE);
-    let num_blocks = input.len() / 16;
-
-    // block 0 xor with iv then encrypt
-    // block 1 xor with block 0 cipher text then encrypt
+// cryptopals style CTR - 64 bit little endian nonce and counter
+fn aes_128_cryptopals_ctr(key: &[u8], nonce: u64) -> Result<Ctr<Aes128>, CryptoError> {
+    Ctr::new(
+        Aes128::new(key)?,
+        &nonce.to_le_bytes(),
+        CounterLayout::LittleEndian64,
+    )
+}
 
-    for i in 0..num_blocks {
-        let mut input_block: Vec<u8> = input[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE].to_vec(); // todo could be an array
+pub fn aes_128_ctr(key: &[u8], nonce: u64, input: Vec<u8>) -> Result<Vec<u8>, CryptoError> {
+    aes_128_cryptopals_ctr(key, nonce)?.encrypt(input)
+}
 
-        let chain = if i == 0 {
-            iv
-        } else {
-            &result[(i - 1) * BLOCK_SIZE..i * BLOCK_SIZE]
-        };
-        xor_in_place(&mut input_block[..], chain);
-        let mut result_block = aes_128_ecb_block(key, &input_block, Encrypt);
-        result.append(&mut result_block);
-    }
-    result
+// see `Ctr::edit`
+pub fn aes_128_ctr_edit(
+    cipher_text: Vec<u8>,
+    key: &[u8],
+    nonce: u64,
+    offset: usize,
+    new_text: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    aes_128_cryptopals_ctr(key, nonce)?.edit(cipher_text, offset, new_text)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::block::cipher::BlockCipher;
 
     #[test]
     fn test_aes_128_ecb() {
@@ -118,7 +78,7 @@ mod tests {
 
         let key = "YELLOW SUBMARINE".as_bytes();
 
-        let plain_text = aes_128_ecb_decrypt(key, &cipher_text);
+        let plain_text = aes_128_ecb_decrypt(key, &cipher_text).unwrap();
 
         let plain = String::from_utf8(plain_text).unwrap();
 
@@ -135,8 +95,8 @@ mod tests {
 
         let key = "YELLOW SUBMARINE".as_bytes();
 
-        let plain_text = aes_128_ecb_decrypt(key, &cipher_text);
-        let encrypted = aes_128_ecb_encrypt(key, plain_text.clone());
+        let plain_text = aes_128_ecb_decrypt(key, &cipher_text).unwrap();
+        let encrypted = aes_128_ecb_encrypt(key, plain_text.clone()).unwrap();
         assert_eq!(encrypted, cipher_text);
 
         let plain = String::from_utf8(plain_text).unwrap();
@@ -155,7 +115,7 @@ mod tests {
         let key = "YELLOW SUBMARINE".as_bytes();
         let iv: [u8; 16] = [0; 16];
 
-        let plain_text = aes_128_cbc_decrypt(key, cipher_text, &iv);
+        let plain_text = aes_128_cbc_decrypt(key, cipher_text, &iv).unwrap();
 
         let plain = String::from_utf8(plain_text).unwrap();
 
@@ -173,9 +133,80 @@ mod tests {
         let key = "YELLOW SUBMARINE".as_bytes();
         let iv: [u8; 16] = [0; 16];
 
-        let plain_text = aes_128_cbc_decrypt(key, cipher_text.clone(), &iv);
+        let plain_text = aes_128_cbc_decrypt(key, cipher_text.clone(), &iv).unwrap();
 
-        let encrypted = aes_128_cbc_encrypt(key, plain_text, &iv);
+        let encrypted = aes_128_cbc_encrypt(key, plain_text, &iv).unwrap();
         assert_eq!(encrypted, cipher_text);
     }
+
+    #[test]
+    fn test_aes_fips_197_vectors() {
+        // FIPS-197 appendix C
+        let plain_text = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let key = hex::decode("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f")
+            .unwrap();
+
+        let ciphers: [(&dyn BlockCipher, &str); 3] = [
+            (
+                &Aes128::new(&key[..16]).unwrap(),
+                "69c4e0d86a7b0430d8cdb78070b4c55a",
+            ),
+            (
+                &Aes192::new(&key[..24]).unwrap(),
+                "dda97ca4864cdfe06eaf70a0ec0d7191",
+            ),
+            (
+                &Aes256::new(&key[..32]).unwrap(),
+                "8ea2b7ca516745bfeafc49904b496089",
+            ),
+        ];
+
+        for (cipher, expected) in ciphers {
+            let mut block = plain_text.clone();
+            cipher.encrypt_block(&mut block).unwrap();
+            assert_eq!(hex::encode(&block), expected);
+
+            cipher.decrypt_block(&mut block).unwrap();
+            assert_eq!(block, plain_text);
+        }
+    }
+
+    #[test]
+    fn test_aes_128_errors() {
+        assert_eq!(
+            aes_128_ecb_encrypt(&[0; 15], vec![]),
+            Err(CryptoError::BadKeyLength {
+                expected: 16,
+                actual: 15
+            })
+        );
+        assert_eq!(
+            aes_128_cbc_encrypt(&[0; 16], vec![], &[0; 15]),
+            Err(CryptoError::BadIvLength {
+                expected: 16,
+                actual: 15
+            })
+        );
+        assert_eq!(
+            aes_128_ecb_decrypt(&[0; 16], &[0; 17]),
+            Err(CryptoError::NotBlockAligned {
+                len: 17,
+                block_size: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_aes_128_ctr() {
+        let cipher_text = base64::decode(
+            "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==",
+        )
+        .unwrap();
+        let key = "YELLOW SUBMARINE".as_bytes();
+
+        let plain_text = aes_128_ctr(key, 0, cipher_text.clone()).unwrap();
+        assert!(plain_text.starts_with(b"Yo, VIP Let's kick it"));
+
+        assert_eq!(aes_128_ctr(key, 0, plain_text).unwrap(), cipher_text);
+    }
 }
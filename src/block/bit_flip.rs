@@ -0,0 +1,174 @@
+use crate::block::{aes_128_cbc_decrypt, aes_128_cbc_encrypt, aes_128_ctr};
+use rand::{thread_rng, Rng};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlipMode {
+    // flipping a cipher text bit flips the same bit a block later, and garbles the block it's in
+    Cbc { block_size: usize },
+    // flipping a cipher text bit flips the same plain text bit and nothing else
+    Ctr,
+}
+
+// Tamper with a cipher text so the `known` plain text at `offset` decrypts as `desired` instead.
+// For CBC the block before the edit gets garbled, so `offset` has to be at least a block in (the iv isn't ours to change)
+// and the edit shouldn't reach back into a block we need intact.
+// None if `known` and `desired` differ in length or the bytes to flip aren't all in the cipher text.
+pub fn flip_bits(
+    mode: FlipMode,
+    cipher_text: &[u8],
+    offset: usize,
+    known: &[u8],
+    desired: &[u8],
+) -> Option<Vec<u8>> {
+    if known.len() != desired.len() {
+        return None;
+    }
+
+    let target = match mode {
+        // can't flip bits in the first CBC block without the iv
+        FlipMode::Cbc { block_size } => offset.checked_sub(block_size)?,
+        FlipMode::Ctr => offset,
+    };
+
+    let mut tampered = cipher_text.to_vec();
+    let flipped = tampered.get_mut(target..target.checked_add(known.len())?)?;
+    for (t, (k, d)) in flipped.iter_mut().zip(known.iter().zip(desired)) {
+        *t ^= k ^ d;
+    }
+    Some(tampered)
+}
+
+pub(crate) const COOKIE_PREFIX: &str = "comment1=cooking%20MCs;userdata=";
+pub(crate) const COOKIE_SUFFIX: &str = ";comment2=%20like%20a%20pound%20of%20bacon";
+
+// the plain text cookie with the user data quoted so it can't add fields of its own
+pub(crate) fn cookie_plain_text(user_data: &str) -> Vec<u8> {
+    let sanitised_input = user_data.replace(';', "%3B").replace('=', "%3D");
+    format!("{COOKIE_PREFIX}{sanitised_input}{COOKIE_SUFFIX}").into_bytes()
+}
+
+// cookie service that can be run in either mode
+struct CookieCutter {
+    mode: FlipMode,
+    key: [u8; 16],
+    iv: [u8; 16],
+    nonce: u64,
+}
+
+impl CookieCutter {
+    fn new(mode: FlipMode) -> Self {
+        let mut key = [0; 16];
+        thread_rng().fill(&mut key);
+
+        let mut iv = [0; 16];
+        thread_rng().fill(&mut iv);
+
+        CookieCutter {
+            mode,
+            key,
+            iv,
+            nonce: thread_rng().gen(),
+        }
+    }
+
+    fn cbc() -> Self {
+        CookieCutter::new(FlipMode::Cbc { block_size: 16 })
+    }
+
+    fn ctr() -> Self {
+        CookieCutter::new(FlipMode::Ctr)
+    }
+
+    fn make_cookie(&self, user_data: &str) -> Vec<u8> {
+        let cookie_bytes = cookie_plain_text(user_data);
+        match self.mode {
+            FlipMode::Cbc { .. } => aes_128_cbc_encrypt(&self.key, cookie_bytes, &self.iv),
+            FlipMode::Ctr => aes_128_ctr(&self.key, self.nonce, cookie_bytes),
+        }
+        .unwrap()
+    }
+
+    fn is_admin(&self, cookie: &[u8]) -> bool {
+        let decrypt = match self.mode {
+            FlipMode::Cbc { .. } => aes_128_cbc_decrypt(&self.key, cookie.to_vec(), &self.iv),
+            FlipMode::Ctr => aes_128_ctr(&self.key, self.nonce, cookie.to_vec()),
+        }
+        .unwrap();
+
+        let result = String::from_utf8_lossy(&decrypt);
+        result.contains(";admin=true;")
+    }
+}
+
+fn flip_my_bits(oracle: &CookieCutter) -> Option<Vec<u8>> {
+    // a whole block for CBC to garble, then something that gets through the sanitising one bit away from what we want
+    let sacrificial = "A".repeat(16);
+    let known = ":admin<true";
+    let desired = ";admin=true";
+
+    let cookie = oracle.make_cookie(&format!("{sacrificial}{known}"));
+    flip_bits(
+        oracle.mode,
+        &cookie,
+        COOKIE_PREFIX.len() + sacrificial.len(),
+        known.as_bytes(),
+        desired.as_bytes(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flip_my_bits() {
+        let oracle = CookieCutter::cbc();
+        let my_bits = flip_my_bits(&oracle).unwrap();
+        assert!(oracle.is_admin(&my_bits))
+    }
+
+    #[test]
+    fn test_flip_my_bits_ctr() {
+        let oracle = CookieCutter::ctr();
+        assert!(!oracle.is_admin(&oracle.make_cookie(";admin=true;")));
+
+        let my_bits = flip_my_bits(&oracle).unwrap();
+        assert!(oracle.is_admin(&my_bits))
+    }
+
+    #[test]
+    fn test_flip_bits_ctr_no_sacrifice() {
+        // CTR doesn't need the sacrificial block, the edit can go anywhere
+        let oracle = CookieCutter::ctr();
+        let cookie = oracle.make_cookie("XadminXtrue");
+        let tampered = flip_bits(
+            FlipMode::Ctr,
+            &cookie,
+            COOKIE_PREFIX.len(),
+            b"XadminXtrue",
+            b";admin=true",
+        )
+        .unwrap();
+        assert!(oracle.is_admin(&tampered));
+    }
+
+    #[test]
+    fn test_flip_bits_out_of_range() {
+        let cbc = FlipMode::Cbc { block_size: 16 };
+        let cipher_text = [0u8; 32];
+
+        assert_eq!(flip_bits(FlipMode::Ctr, &cipher_text, 0, b"ab", b"a"), None);
+        assert_eq!(flip_bits(cbc, &cipher_text, 8, b"a", b"b"), None);
+        assert_eq!(
+            flip_bits(FlipMode::Ctr, &cipher_text, 31, b"ab", b"cd"),
+            None
+        );
+        assert_eq!(flip_bits(cbc, &cipher_text, 48, b"a", b"b"), None);
+
+        // the last byte of the last block flips the same byte in CTR, the block before in CBC
+        let ctr = flip_bits(FlipMode::Ctr, &cipher_text, 31, b"a", b"b").unwrap();
+        assert_eq!(ctr[31], b'a' ^ b'b');
+        let cbc = flip_bits(cbc, &cipher_text, 31, b"a", b"b").unwrap();
+        assert_eq!(cbc[15], b'a' ^ b'b');
+    }
+}
@@ -1,10 +1,10 @@
-use crate::block::aes_128_ecb_encrypt;
 use crate::block::byte_at_a_time_simple::{discover_block_size, SUFFIX};
+use crate::block::{ecb_encrypt, Aes128, Pkcs7};
 use rand::Rng;
 
 struct Oracle {
     prefix: Vec<u8>,
-    key: [u8; 16],
+    cipher: Aes128,
     suffix: Vec<u8>,
 }
 
@@ -19,7 +19,7 @@ impl Oracle {
         rand::thread_rng().fill(&mut prefix[..]);
 
         Oracle {
-            key,
+            cipher: Aes128::new(&key).unwrap(),
             suffix,
             prefix,
         }
@@ -31,7 +31,7 @@ impl Oracle {
         payload.extend_from_slice(&input);
         payload.extend_from_slice(&self.suffix);
 
-        aes_128_ecb_encrypt(&self.key, payload)
+        ecb_encrypt(&self.cipher, &Pkcs7, payload).unwrap()
     }
 }
 
@@ -120,8 +120,6 @@ mod tests {
 
         assert_eq!(data.len(), oracle.suffix.len());
 
-        for i in 0..oracle.suffix.len() {
-            assert_eq!(data[i], oracle.suffix[i]);
-        }
+        assert_eq!(data, oracle.suffix);
     }
 }
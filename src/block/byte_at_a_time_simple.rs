@@ -1,11 +1,11 @@
-use crate::block::aes_128_ecb_encrypt;
 use crate::block::detect_ecb::HashCount;
+use crate::block::{ecb_encrypt, Aes128, Pkcs7};
 use rand::Rng;
 
 pub const SUFFIX: &str = "Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkgaGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBqdXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUgYnkK";
 
 struct Oracle {
-    key: [u8; 16],
+    cipher: Aes128,
     suffix: Vec<u8>,
 }
 
@@ -14,12 +14,15 @@ impl Oracle {
         let mut key = [0u8; 16];
         rand::thread_rng().fill(&mut key);
         let suffix = base64::decode(SUFFIX).unwrap();
-        Oracle { key, suffix }
+        Oracle {
+            cipher: Aes128::new(&key).unwrap(),
+            suffix,
+        }
     }
 
     fn aes_128_ecb(&self, mut input: Vec<u8>) -> Vec<u8> {
         input.extend_from_slice(&self.suffix);
-        aes_128_ecb_encrypt(&self.key, input)
+        ecb_encrypt(&self.cipher, &Pkcs7, input).unwrap()
     }
 }
 
@@ -116,7 +119,10 @@ mod tests {
 
             let suffix = vec![0xaau8; i];
 
-            let oracle = Oracle { key, suffix };
+            let oracle = Oracle {
+                cipher: Aes128::new(&key).unwrap(),
+                suffix,
+            };
 
             let (block_size, data_length) = discover_block_size(|input| oracle.aes_128_ecb(input));
             assert_eq!(16, block_size);
@@ -127,7 +133,7 @@ mod tests {
     #[test]
     fn test_is_ecb() {
         let oracle = Oracle::new();
-        assert_eq!(true, is_ecb(|input| oracle.aes_128_ecb(input)));
+        assert!(is_ecb(|input| oracle.aes_128_ecb(input)));
     }
 
     #[test]
@@ -137,8 +143,6 @@ mod tests {
 
         assert_eq!(data.len(), oracle.suffix.len());
 
-        for i in 0..oracle.suffix.len() {
-            assert_eq!(data[i], oracle.suffix[i]);
-        }
+        assert_eq!(data, oracle.suffix);
     }
 }
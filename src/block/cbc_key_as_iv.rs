@@ -0,0 +1,94 @@
+use crate::block::bit_flip::cookie_plain_text;
+use crate::block::{aes_128_cbc_decrypt, aes_128_cbc_encrypt};
+use crate::error::CryptoError;
+use rand::{thread_rng, Rng};
+
+// Cryptopals 27 - recover a CBC key that's also used as the iv from an error that echoes the plain text.
+// Send C1 || 0 || C1 then P'1 = D(C1) ^ key and P'3 = D(C1) ^ 0, so P'1 ^ P'3 is the key.
+// The original last two blocks go on the end so the padding still checks out and we get as far as the ascii check.
+pub fn recover_key_as_iv<T, F: Fn(&[u8]) -> Result<T, CryptoError>>(
+    oracle: F,
+    cipher_text: &[u8],
+    block_size: usize,
+) -> Option<Vec<u8>> {
+    if block_size == 0 || cipher_text.len() < 2 * block_size {
+        return None;
+    }
+
+    let c1 = &cipher_text[..block_size];
+    let mut tampered = c1.to_vec();
+    tampered.extend(vec![0; block_size]);
+    tampered.extend(c1);
+    tampered.extend(&cipher_text[cipher_text.len() - 2 * block_size..]);
+
+    match oracle(&tampered) {
+        Err(CryptoError::NotAscii { plain_text }) if plain_text.len() >= 3 * block_size => Some(
+            plain_text[..block_size]
+                .iter()
+                .zip(&plain_text[2 * block_size..3 * block_size])
+                .map(|(p1, p3)| p1 ^ p3)
+                .collect(),
+        ),
+        // it decrypted to ascii by chance, echoed too little, or failed some other way
+        _ => None,
+    }
+}
+
+// the CBC cookie service but saving on random bytes by using the key as the iv
+struct KeyAsIvCookieCutter {
+    key: [u8; 16],
+}
+
+impl Default for KeyAsIvCookieCutter {
+    fn default() -> Self {
+        let mut key = [0; 16];
+        thread_rng().fill(&mut key);
+
+        KeyAsIvCookieCutter { key }
+    }
+}
+
+impl KeyAsIvCookieCutter {
+    fn make_cookie(&self, user_data: &str) -> Vec<u8> {
+        aes_128_cbc_encrypt(&self.key, cookie_plain_text(user_data), &self.key).unwrap()
+    }
+
+    fn is_admin(&self, cookie: &[u8]) -> Result<bool, CryptoError> {
+        let plain_text = aes_128_cbc_decrypt(&self.key, cookie.to_vec(), &self.key)?;
+        if !plain_text.is_ascii() {
+            return Err(CryptoError::NotAscii { plain_text });
+        }
+
+        Ok(String::from_utf8_lossy(&plain_text).contains(";admin=true;"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recover_key_as_iv() {
+        let oracle = KeyAsIvCookieCutter::default();
+        let cookie = oracle.make_cookie("hello");
+        assert_eq!(oracle.is_admin(&cookie), Ok(false));
+
+        let key = recover_key_as_iv(|c| oracle.is_admin(c), &cookie, 16).unwrap();
+        assert_eq!(key, oracle.key);
+
+        // with the key we can make our own admin cookie
+        let forged = aes_128_cbc_encrypt(&key, b";admin=true;".to_vec(), &key).unwrap();
+        assert_eq!(oracle.is_admin(&forged), Ok(true));
+    }
+
+    #[test]
+    fn test_recover_key_as_iv_too_short() {
+        let oracle = KeyAsIvCookieCutter::default();
+        let cookie = oracle.make_cookie("");
+        assert_eq!(
+            recover_key_as_iv(|c| oracle.is_admin(c), &cookie[..16], 16),
+            None
+        );
+        assert_eq!(recover_key_as_iv(|c| oracle.is_admin(c), &cookie, 0), None);
+    }
+}
@@ -0,0 +1,26 @@
+use crate::error::CryptoError;
+
+// A keyed block cipher operating on a single block at a time.
+// The chaining modes in `block::modes` are written against this so they work for AES and toy ciphers alike.
+// Errors are for backends that can fail underneath (e.g. openssl), the cipher itself never rejects a block.
+pub trait BlockCipher {
+    fn block_size(&self) -> usize;
+
+    fn encrypt_block(&self, block: &mut [u8]) -> Result<(), CryptoError>;
+
+    fn decrypt_block(&self, block: &mut [u8]) -> Result<(), CryptoError>;
+
+    // whole number of blocks, each processed independently (i.e. ECB)
+    // override when the cipher can do better than one block at a time
+    fn encrypt_blocks(&self, blocks: &mut [u8]) -> Result<(), CryptoError> {
+        blocks
+            .chunks_exact_mut(self.block_size())
+            .try_for_each(|block| self.encrypt_block(block))
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [u8]) -> Result<(), CryptoError> {
+        blocks
+            .chunks_exact_mut(self.block_size())
+            .try_for_each(|block| self.decrypt_block(block))
+    }
+}
@@ -0,0 +1,333 @@
+use crate::block::cipher::BlockCipher;
+use crate::block::modes::xor_in_place;
+use crate::error::CryptoError;
+
+// How the nonce and block counter are laid out in each counter block.
+// The nonce fills the bytes the counter doesn't, so with 16 byte blocks that's 8 bytes for the 64 bit layouts and 12 for BigEndian32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterLayout {
+    // nonce then a 64 bit little endian counter - the cryptopals layout
+    LittleEndian64,
+    // nonce then a 64 bit big endian counter
+    BigEndian64,
+    // nonce then a 32 bit big endian counter that wraps, as used by GCM
+    BigEndian32,
+}
+
+impl CounterLayout {
+    fn counter_len(&self) -> usize {
+        match self {
+            CounterLayout::LittleEndian64 | CounterLayout::BigEndian64 => 8,
+            CounterLayout::BigEndian32 => 4,
+        }
+    }
+
+    fn write_counter(&self, counter: u64, out: &mut [u8]) {
+        match self {
+            CounterLayout::LittleEndian64 => out.copy_from_slice(&counter.to_le_bytes()),
+            CounterLayout::BigEndian64 => out.copy_from_slice(&counter.to_be_bytes()),
+            CounterLayout::BigEndian32 => out.copy_from_slice(&(counter as u32).to_be_bytes()),
+        }
+    }
+}
+
+pub struct Ctr<C: BlockCipher> {
+    cipher: C,
+    nonce: Vec<u8>,
+    layout: CounterLayout,
+}
+
+impl<C: BlockCipher> Ctr<C> {
+    pub fn new(cipher: C, nonce: &[u8], layout: CounterLayout) -> Result<Self, CryptoError> {
+        let (block_size, counter_len) = (cipher.block_size(), layout.counter_len());
+        if block_size < counter_len {
+            return Err(CryptoError::BlockTooSmall {
+                block_size,
+                needed: counter_len,
+            });
+        }
+
+        let expected = block_size - counter_len;
+        if nonce.len() != expected {
+            return Err(CryptoError::BadNonceLength {
+                expected,
+                actual: nonce.len(),
+            });
+        }
+
+        Ok(Ctr {
+            cipher,
+            nonce: nonce.to_vec(),
+            layout,
+        })
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.cipher.block_size()
+    }
+
+    pub fn counter_block(&self, counter: u64) -> Vec<u8> {
+        let mut block = self.nonce.clone();
+        block.resize(self.block_size(), 0);
+        self.layout
+            .write_counter(counter, &mut block[self.nonce.len()..]);
+        block
+    }
+
+    // the encrypted counter block, i.e. keystream bytes counter * block_size ..
+    pub fn keystream_block(&self, counter: u64) -> Result<Vec<u8>, CryptoError> {
+        let mut block = self.counter_block(counter);
+        self.cipher.encrypt_block(&mut block)?;
+        Ok(block)
+    }
+
+    // keystream starting from an arbitrary byte offset
+    pub fn keystream(&self, offset: u64) -> Keystream<'_, C> {
+        Keystream {
+            ctr: self,
+            block: None,
+            offset,
+        }
+    }
+
+    // xor the keystream into data which sits at `offset` in the stream - encrypt and decrypt are the same thing
+    pub fn apply_keystream(&self, data: &mut [u8], offset: u64) -> Result<(), CryptoError> {
+        let block_size = self.block_size() as u64;
+        let mut counter = offset / block_size;
+        let skip = (offset % block_size) as usize;
+
+        // a partial first block if the offset isn't aligned, then whole blocks
+        let (head, rest) = data.split_at_mut((self.block_size() - skip).min(data.len()));
+        xor_in_place(head, &self.keystream_block(counter)?[skip..]);
+
+        for chunk in rest.chunks_mut(self.block_size()) {
+            counter += 1;
+            xor_in_place(chunk, &self.keystream_block(counter)?);
+        }
+        Ok(())
+    }
+
+    pub fn encrypt(&self, mut input: Vec<u8>) -> Result<Vec<u8>, CryptoError> {
+        self.apply_keystream(&mut input, 0)?;
+        Ok(input)
+    }
+
+    pub fn decrypt(&self, input: Vec<u8>) -> Result<Vec<u8>, CryptoError> {
+        self.encrypt(input)
+    }
+
+    // Overwrite the plain text at `offset` with `new_text`, only the edited bytes get re-encrypted.
+    // Writing past the end grows the cipher text, any gap is filled with encrypted zeros.
+    pub fn edit(
+        &self,
+        mut cipher_text: Vec<u8>,
+        offset: usize,
+        new_text: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let end = offset + new_text.len();
+        if end > cipher_text.len() {
+            let old_len = cipher_text.len();
+            cipher_text.resize(end, 0);
+            self.apply_keystream(&mut cipher_text[old_len..], old_len as u64)?;
+        }
+
+        let edited = &mut cipher_text[offset..end];
+        edited.copy_from_slice(new_text);
+        self.apply_keystream(edited, offset as u64)?;
+        Ok(cipher_text)
+    }
+}
+
+// Seekable byte by byte keystream, the current block is cached so it only encrypts once per block.
+// Blocks are loaded lazily and the iterator ends early if the cipher fails.
+pub struct Keystream<'a, C: BlockCipher> {
+    ctr: &'a Ctr<C>,
+    block: Option<(u64, Vec<u8>)>,
+    offset: u64,
+}
+
+impl<C: BlockCipher> Keystream<'_, C> {
+    pub fn position(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn seek(&mut self, offset: u64) {
+        self.offset = offset;
+    }
+}
+
+impl<C: BlockCipher> Iterator for Keystream<'_, C> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let block_size = self.ctr.block_size() as u64;
+        let counter = self.offset / block_size;
+        if !matches!(&self.block, Some((cached, _)) if *cached == counter) {
+            self.block = Some((counter, self.ctr.keystream_block(counter).ok()?));
+        }
+
+        let (_, block) = self.block.as_ref()?;
+        let b = block[(self.offset % block_size) as usize];
+        self.offset += 1;
+        Some(b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::Aes128;
+
+    const CHALLENGE_18: &str =
+        "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==";
+
+    fn cryptopals_ctr() -> Ctr<Aes128> {
+        let cipher = Aes128::new("YELLOW SUBMARINE".as_bytes()).unwrap();
+        Ctr::new(cipher, &0u64.to_le_bytes(), CounterLayout::LittleEndian64).unwrap()
+    }
+
+    #[test]
+    fn test_ctr_challenge_18() {
+        let ctr = cryptopals_ctr();
+        let cipher_text = base64::decode(CHALLENGE_18).unwrap();
+
+        let plain_text = ctr.decrypt(cipher_text.clone()).unwrap();
+        assert_eq!(
+            String::from_utf8(plain_text.clone()).unwrap(),
+            "Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby "
+        );
+
+        assert_eq!(ctr.encrypt(plain_text).unwrap(), cipher_text);
+    }
+
+    #[test]
+    fn test_ctr_nist_sp800_38a() {
+        // F.5.1 CTR-AES128.Encrypt, the initial counter block is f0f1...feff
+        let key = hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let nonce = hex::decode("f0f1f2f3f4f5f6f7f8f9fafb").unwrap();
+        let plain_text =
+            hex::decode("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")
+                .unwrap();
+
+        let ctr = Ctr::new(
+            Aes128::new(&key).unwrap(),
+            &nonce,
+            CounterLayout::BigEndian32,
+        )
+        .unwrap();
+
+        let mut data = plain_text;
+        ctr.apply_keystream(&mut data, 0xfcfdfeff * 16).unwrap();
+        assert_eq!(
+            hex::encode(data),
+            "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff"
+        );
+    }
+
+    #[test]
+    fn test_counter_layouts() {
+        let cipher = || Aes128::new(&[0; 16]).unwrap();
+        let counter = 0x0102030405060708;
+
+        let le = Ctr::new(cipher(), &[0xaa; 8], CounterLayout::LittleEndian64).unwrap();
+        assert_eq!(
+            hex::encode(le.counter_block(counter)),
+            "aaaaaaaaaaaaaaaa0807060504030201"
+        );
+
+        let be = Ctr::new(cipher(), &[0xaa; 8], CounterLayout::BigEndian64).unwrap();
+        assert_eq!(
+            hex::encode(be.counter_block(counter)),
+            "aaaaaaaaaaaaaaaa0102030405060708"
+        );
+
+        let be32 = Ctr::new(cipher(), &[0xaa; 12], CounterLayout::BigEndian32).unwrap();
+        assert_eq!(
+            hex::encode(be32.counter_block(0xffffffff + 2)),
+            "aaaaaaaaaaaaaaaaaaaaaaaa00000001"
+        );
+
+        assert_eq!(
+            Ctr::new(cipher(), &[0xaa; 8], CounterLayout::BigEndian32).err(),
+            Some(CryptoError::BadNonceLength {
+                expected: 12,
+                actual: 8
+            })
+        );
+    }
+
+    // 4 byte blocks, too small for a 64 bit counter
+    struct TinyCipher;
+
+    impl BlockCipher for TinyCipher {
+        fn block_size(&self) -> usize {
+            4
+        }
+
+        fn encrypt_block(&self, _block: &mut [u8]) -> Result<(), CryptoError> {
+            Ok(())
+        }
+
+        fn decrypt_block(&self, _block: &mut [u8]) -> Result<(), CryptoError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_block_too_small_for_counter() {
+        assert_eq!(
+            Ctr::new(TinyCipher, &[], CounterLayout::LittleEndian64).err(),
+            Some(CryptoError::BlockTooSmall {
+                block_size: 4,
+                needed: 8
+            })
+        );
+        assert!(Ctr::new(TinyCipher, &[], CounterLayout::BigEndian32).is_ok());
+    }
+
+    #[test]
+    fn test_ctr_edit() {
+        let ctr = cryptopals_ctr();
+        let cipher_text = base64::decode(CHALLENGE_18).unwrap();
+
+        let edited = ctr.edit(cipher_text.clone(), 4, b"RAP").unwrap();
+        assert_eq!(edited.len(), cipher_text.len());
+        assert_eq!(edited[..4], cipher_text[..4]);
+        assert_eq!(edited[7..], cipher_text[7..]);
+        assert!(ctr
+            .decrypt(edited)
+            .unwrap()
+            .starts_with(b"Yo, RAP Let's kick it"));
+
+        // past the end, with a gap
+        let grown = ctr
+            .edit(cipher_text.clone(), cipher_text.len() + 2, b"!!")
+            .unwrap();
+        let plain_text = ctr.decrypt(grown).unwrap();
+        assert!(plain_text.ends_with(b"baby \0\0!!"));
+    }
+
+    #[test]
+    fn test_keystream_seek() {
+        let ctr = cryptopals_ctr();
+        let cipher_text = base64::decode(CHALLENGE_18).unwrap();
+
+        let mut keystream = ctr.keystream(0);
+        let full: Vec<u8> = keystream.by_ref().take(cipher_text.len()).collect();
+        assert_eq!(keystream.position(), cipher_text.len() as u64);
+
+        let mut plain_text = cipher_text.clone();
+        xor_in_place(&mut plain_text, &full);
+        assert_eq!(plain_text, ctr.decrypt(cipher_text.clone()).unwrap());
+
+        for offset in [0, 5, 15, 16, 17, 40] {
+            keystream.seek(offset);
+            let tail: Vec<u8> = keystream.by_ref().take(10).collect();
+            assert_eq!(tail, full[offset as usize..offset as usize + 10]);
+
+            let mut part = cipher_text[offset as usize..].to_vec();
+            ctr.apply_keystream(&mut part, offset).unwrap();
+            assert_eq!(part, plain_text[offset as usize..]);
+        }
+    }
+}
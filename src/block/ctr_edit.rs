@@ -0,0 +1,69 @@
+use crate::block::aes_128_ctr_edit;
+use rand::{thread_rng, Rng};
+
+// Cryptopals 25 - an exposed edit(cipher_text, offset, new_text) gives the plain text away.
+// Editing in our own plain text gets us the keystream at that offset, so write zeros over everything
+// and what comes back is the keystream itself.
+pub fn break_ctr_edit<F: Fn(Vec<u8>, usize, &[u8]) -> Vec<u8>>(
+    edit: F,
+    cipher_text: &[u8],
+) -> Vec<u8> {
+    let keystream = edit(cipher_text.to_vec(), 0, &vec![0; cipher_text.len()]);
+
+    cipher_text
+        .iter()
+        .zip(keystream)
+        .map(|(c, k)| c ^ k)
+        .collect()
+}
+
+// disk encryption with a seekable edit - the key and nonce stay inside, only edit is handed out
+struct SeekableStore {
+    key: [u8; 16],
+    nonce: u64,
+}
+
+impl Default for SeekableStore {
+    fn default() -> Self {
+        let mut key = [0; 16];
+        thread_rng().fill(&mut key);
+
+        SeekableStore {
+            key,
+            nonce: thread_rng().gen(),
+        }
+    }
+}
+
+impl SeekableStore {
+    fn encrypt(&self, plain_text: &[u8]) -> Vec<u8> {
+        self.edit(Vec::new(), 0, plain_text)
+    }
+
+    fn edit(&self, cipher_text: Vec<u8>, offset: usize, new_text: &[u8]) -> Vec<u8> {
+        aes_128_ctr_edit(cipher_text, &self.key, self.nonce, offset, new_text).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::aes_128_ecb_decrypt;
+    use std::fs::read_to_string;
+
+    #[test]
+    fn test_break_ctr_edit() {
+        // challenge 25 uses the challenge 7 plain text
+        let f = read_to_string("./challenge-data/7.txt").unwrap();
+        let f: String = f.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+        let plain_text =
+            aes_128_ecb_decrypt(b"YELLOW SUBMARINE", &base64::decode(f).unwrap()).unwrap();
+
+        let store = SeekableStore::default();
+        let cipher_text = store.encrypt(&plain_text);
+        assert_ne!(cipher_text, plain_text);
+
+        let recovered = break_ctr_edit(|c, offset, new| store.edit(c, offset, new), &cipher_text);
+        assert_eq!(recovered, plain_text);
+    }
+}
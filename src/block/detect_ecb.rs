@@ -1,4 +1,3 @@
-use openssl::rand::rand_bytes;
 use std::collections::HashMap;
 
 use crate::block::{aes_128_cbc_encrypt, aes_128_ecb_encrypt};
@@ -53,7 +52,7 @@ fn encryption_oracle(input: Vec<u8>) -> Vec<u8> {
 
 fn leaky_encryption_oracle(mut input: Vec<u8>) -> (Vec<u8>, bool) {
     let mut key: [u8; 16] = [0; 16];
-    rand_bytes(&mut key).unwrap();
+    thread_rng().fill(&mut key);
 
     let mut padded_input = Vec::with_capacity(input.len() + 20);
     let prefix_len = thread_rng().gen_range(5..10);
@@ -68,11 +67,11 @@ fn leaky_encryption_oracle(mut input: Vec<u8>) -> (Vec<u8>, bool) {
 
     let use_ecb: bool = thread_rng().gen();
     if use_ecb {
-        (aes_128_ecb_encrypt(&key, padded_input), use_ecb)
+        (aes_128_ecb_encrypt(&key, padded_input).unwrap(), use_ecb)
     } else {
         let mut iv: [u8; 16] = [0; 16];
-        rand_bytes(&mut iv).unwrap();
-        (aes_128_cbc_encrypt(&key, input, &iv), use_ecb)
+        thread_rng().fill(&mut iv);
+        (aes_128_cbc_encrypt(&key, input, &iv).unwrap(), use_ecb)
     }
 }
 
@@ -99,7 +98,7 @@ mod tests {
         let data: Vec<_> =
             io::BufReader::new(std::fs::File::open("./challenge-data/8.txt").unwrap())
                 .lines()
-                .filter_map(|r| r.ok())
+                .map_while(Result::ok)
                 .map(|s| hex::decode(s).unwrap())
                 .collect();
 
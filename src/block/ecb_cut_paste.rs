@@ -2,7 +2,7 @@ use crate::block::{aes_128_ecb_decrypt, aes_128_ecb_encrypt};
 use std::collections::HashMap;
 
 fn cookie_parse(s: &str) -> HashMap<String, String> {
-    let parts: Vec<_> = s.split(|c: char| c == '=' || c == '&').collect();
+    let parts: Vec<_> = s.split(['=', '&']).collect();
 
     let mut result = HashMap::new();
     for i in 0..(parts.len() / 2) {
@@ -35,11 +35,11 @@ impl ProfileOracle {
     fn enc_profile_for(&self, email: &str) -> Vec<u8> {
         let profile = profile_for(email).unwrap();
 
-        aes_128_ecb_encrypt(&self.key, profile.into_bytes())
+        aes_128_ecb_encrypt(&self.key, profile.into_bytes()).unwrap()
     }
 
     fn is_admin_profile(&self, data: &[u8]) -> bool {
-        let decrypt = aes_128_ecb_decrypt(&self.key, data);
+        let decrypt = aes_128_ecb_decrypt(&self.key, data).unwrap();
 
         let profile_cookie = String::from_utf8(decrypt).unwrap();
 
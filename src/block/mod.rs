@@ -1,12 +1,40 @@
 mod aes;
+mod bit_flip;
 mod byte_at_a_time_hard;
 mod byte_at_a_time_simple;
-mod cbc_bit_flip;
+mod cbc_key_as_iv;
+mod cipher;
+mod ctr;
+mod ctr_edit;
 mod detect_ecb;
 mod ecb_cut_paste;
+mod modes;
+#[cfg(feature = "openssl")]
+mod openssl_aes;
 mod padding;
+mod padding_oracle;
+#[cfg(feature = "soft-aes")]
+mod soft_aes;
 
 pub use aes::aes_128_cbc_decrypt;
 pub use aes::aes_128_cbc_encrypt;
+pub use aes::aes_128_ctr;
+pub use aes::aes_128_ctr_edit;
 pub use aes::aes_128_ecb_decrypt;
 pub use aes::aes_128_ecb_encrypt;
+pub use aes::{Aes, Aes128, Aes192, Aes256};
+pub use bit_flip::{flip_bits, FlipMode};
+pub use cbc_key_as_iv::recover_key_as_iv;
+pub use cipher::BlockCipher;
+pub use ctr::{CounterLayout, Ctr, Keystream};
+pub use ctr_edit::break_ctr_edit;
+pub use modes::{
+    cbc_decrypt, cbc_decrypt_blocks, cbc_encrypt, cbc_encrypt_blocks, ecb_decrypt,
+    ecb_decrypt_blocks, ecb_encrypt, ecb_encrypt_blocks,
+};
+#[cfg(feature = "openssl")]
+pub use openssl_aes::OpensslAes;
+pub use padding::{AnsiX923, Iso10126, Iso7816, Padding, Pkcs7, ZeroPadding};
+pub use padding_oracle::{padding_oracle_decrypt, padding_oracle_encrypt, recover_intermediate};
+#[cfg(feature = "soft-aes")]
+pub use soft_aes::SoftAes;
@@ -0,0 +1,268 @@
+use crate::block::cipher::BlockCipher;
+use crate::block::padding::Padding;
+use crate::error::CryptoError;
+
+pub(crate) fn xor_in_place(data: &mut [u8], key: &[u8]) {
+    data.iter_mut().zip(key).for_each(|(b, k)| *b ^= k);
+}
+
+fn check_aligned<C: BlockCipher + ?Sized>(cipher: &C, data: &[u8]) -> Result<(), CryptoError> {
+    let block_size = cipher.block_size();
+    if data.len().is_multiple_of(block_size) {
+        Ok(())
+    } else {
+        Err(CryptoError::NotBlockAligned {
+            len: data.len(),
+            block_size,
+        })
+    }
+}
+
+fn check_iv<C: BlockCipher + ?Sized>(cipher: &C, iv: &[u8]) -> Result<(), CryptoError> {
+    if iv.len() == cipher.block_size() {
+        Ok(())
+    } else {
+        Err(CryptoError::BadIvLength {
+            expected: cipher.block_size(),
+            actual: iv.len(),
+        })
+    }
+}
+
+// The `_blocks` variants work in place on a caller owned buffer that is already a whole number of blocks.
+// The Vec variants add/strip padding around them.
+
+pub fn ecb_encrypt_blocks<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    blocks: &mut [u8],
+) -> Result<(), CryptoError> {
+    check_aligned(cipher, blocks)?;
+    cipher.encrypt_blocks(blocks)
+}
+
+pub fn ecb_decrypt_blocks<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    blocks: &mut [u8],
+) -> Result<(), CryptoError> {
+    check_aligned(cipher, blocks)?;
+    cipher.decrypt_blocks(blocks)
+}
+
+pub fn cbc_encrypt_blocks<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    iv: &[u8],
+    blocks: &mut [u8],
+) -> Result<(), CryptoError> {
+    check_iv(cipher, iv)?;
+    check_aligned(cipher, blocks)?;
+
+    // block 0 xor with iv then encrypt
+    // block 1 xor with block 0 cipher text then encrypt
+
+    let mut prev: &[u8] = iv;
+    for block in blocks.chunks_exact_mut(cipher.block_size()) {
+        xor_in_place(block, prev);
+        cipher.encrypt_block(block)?;
+        prev = block;
+    }
+    Ok(())
+}
+
+pub fn cbc_decrypt_blocks<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    iv: &[u8],
+    blocks: &mut [u8],
+) -> Result<(), CryptoError> {
+    check_iv(cipher, iv)?;
+    check_aligned(cipher, blocks)?;
+    let block_size = cipher.block_size();
+
+    // decrypt everything in one go, then walk backwards xoring in the previous cipher text block
+    // block n plain = D(block n) ^ block n-1 cipher, block 0 plain = D(block 0) ^ iv
+    let cipher_text = blocks.to_vec();
+    cipher.decrypt_blocks(blocks)?;
+
+    let chains = std::iter::once(iv).chain(cipher_text.chunks_exact(block_size));
+    blocks
+        .chunks_exact_mut(block_size)
+        .zip(chains)
+        .for_each(|(block, chain)| xor_in_place(block, chain));
+    Ok(())
+}
+
+// padding always leaves a whole number of blocks so only the cipher itself can fail
+pub fn ecb_encrypt<C: BlockCipher + ?Sized, P: Padding + ?Sized>(
+    cipher: &C,
+    padding: &P,
+    mut input: Vec<u8>,
+) -> Result<Vec<u8>, CryptoError> {
+    padding.pad(&mut input, cipher.block_size());
+    cipher.encrypt_blocks(&mut input)?;
+    Ok(input)
+}
+
+pub fn ecb_decrypt<C: BlockCipher + ?Sized, P: Padding + ?Sized>(
+    cipher: &C,
+    padding: &P,
+    input: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let mut output = input.to_vec();
+    ecb_decrypt_blocks(cipher, &mut output)?;
+
+    padding.strip(&mut output, cipher.block_size())?;
+    Ok(output)
+}
+
+pub fn cbc_encrypt<C: BlockCipher + ?Sized, P: Padding + ?Sized>(
+    cipher: &C,
+    padding: &P,
+    mut input: Vec<u8>,
+    iv: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    padding.pad(&mut input, cipher.block_size());
+    cbc_encrypt_blocks(cipher, iv, &mut input)?;
+    Ok(input)
+}
+
+pub fn cbc_decrypt<C: BlockCipher + ?Sized, P: Padding + ?Sized>(
+    cipher: &C,
+    padding: &P,
+    mut input: Vec<u8>,
+    iv: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    cbc_decrypt_blocks(cipher, iv, &mut input)?;
+
+    padding.strip(&mut input, cipher.block_size())?;
+    Ok(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::padding::{AnsiX923, Iso7816, Pkcs7};
+
+    // 8 byte toy cipher - xor with the key then rotate the block
+    struct ToyCipher {
+        key: [u8; 8],
+    }
+
+    impl BlockCipher for ToyCipher {
+        fn block_size(&self) -> usize {
+            8
+        }
+
+        fn encrypt_block(&self, block: &mut [u8]) -> Result<(), CryptoError> {
+            xor_in_place(block, &self.key);
+            block.rotate_left(3);
+            Ok(())
+        }
+
+        fn decrypt_block(&self, block: &mut [u8]) -> Result<(), CryptoError> {
+            block.rotate_right(3);
+            xor_in_place(block, &self.key);
+            Ok(())
+        }
+    }
+
+    const TOY: ToyCipher = ToyCipher { key: *b"toy key!" };
+
+    #[test]
+    fn test_ecb_toy_cipher_both_ways() {
+        let plain_text = b"I'm back and I'm ringin' the bell".to_vec();
+
+        let encrypted = ecb_encrypt(&TOY, &Pkcs7, plain_text.clone()).unwrap();
+        assert_eq!(encrypted.len(), 40);
+        assert_ne!(encrypted[..plain_text.len()], plain_text[..]);
+
+        assert_eq!(ecb_decrypt(&TOY, &Pkcs7, &encrypted), Ok(plain_text));
+    }
+
+    #[test]
+    fn test_cbc_toy_cipher_both_ways() {
+        let plain_text = b"I'm back and I'm ringin' the bell".to_vec();
+        let iv = [7u8; 8];
+
+        let encrypted = cbc_encrypt(&TOY, &Pkcs7, plain_text.clone(), &iv).unwrap();
+        assert_eq!(encrypted.len(), 40);
+
+        assert_eq!(cbc_decrypt(&TOY, &Pkcs7, encrypted, &iv), Ok(plain_text));
+    }
+
+    #[test]
+    fn test_ecb_repeats_cbc_does_not() {
+        let plain_text = vec![0xaa; 64];
+        let as_dyn: &dyn BlockCipher = &TOY;
+
+        let ecb = ecb_encrypt(as_dyn, &Pkcs7, plain_text.clone()).unwrap();
+        let cbc = cbc_encrypt(as_dyn, &Pkcs7, plain_text, &[0; 8]).unwrap();
+
+        let count = |data: &[u8]| {
+            let mut blocks: Vec<_> = data.chunks(8).collect();
+            blocks.sort();
+            blocks.dedup();
+            blocks.len()
+        };
+        assert_eq!(count(&ecb), 2);
+        assert_eq!(count(&cbc), 9);
+    }
+
+    #[test]
+    fn test_cbc_blocks_in_place() {
+        let plain_text = b"YELLOW SUBMARINEYELLOW SUBMARINE".to_vec();
+        let iv = [1u8; 8];
+
+        let mut buffer = plain_text.clone();
+        cbc_encrypt_blocks(&TOY, &iv, &mut buffer).unwrap();
+        assert_eq!(
+            buffer[..],
+            cbc_encrypt(&TOY, &Pkcs7, plain_text.clone(), &iv).unwrap()[..plain_text.len()]
+        );
+
+        cbc_decrypt_blocks(&TOY, &iv, &mut buffer).unwrap();
+        assert_eq!(buffer, plain_text);
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        let encrypted = ecb_encrypt(&TOY, &Pkcs7, b"YELLOW SUBMARINE".to_vec()).unwrap();
+
+        assert_eq!(
+            ecb_decrypt(&TOY, &Pkcs7, &encrypted[..20]),
+            Err(CryptoError::NotBlockAligned {
+                len: 20,
+                block_size: 8
+            })
+        );
+        assert_eq!(
+            cbc_encrypt(&TOY, &Pkcs7, encrypted.clone(), &[0; 16]),
+            Err(CryptoError::BadIvLength {
+                expected: 8,
+                actual: 16
+            })
+        );
+
+        // the final block is all padding, drop it and the last plain text block is read as padding
+        assert!(matches!(
+            ecb_decrypt(&TOY, &Pkcs7, &encrypted[..16]),
+            Err(CryptoError::InvalidPadding(_))
+        ));
+    }
+
+    #[test]
+    fn test_cbc_other_padding() {
+        let plain_text = b"I'm back and I'm ringin' the bell".to_vec();
+        let iv = [7u8; 8];
+
+        let encrypted = cbc_encrypt(&TOY, &AnsiX923, plain_text.clone(), &iv).unwrap();
+        assert_eq!(
+            cbc_decrypt(&TOY, &AnsiX923, encrypted.clone(), &iv),
+            Ok(plain_text)
+        );
+
+        // X.923 ends 00 .. 07, ISO 7816 wants 80 00 .. 00
+        assert!(matches!(
+            cbc_decrypt(&TOY, &Iso7816, encrypted, &iv),
+            Err(CryptoError::InvalidPadding(_))
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+use openssl::symm::{Cipher, Crypter, Mode};
+use std::cell::RefCell;
+
+use crate::block::aes::BLOCK_SIZE;
+use crate::block::cipher::BlockCipher;
+use crate::error::CryptoError;
+
+// bulk calls are fed through openssl this many bytes at a time
+const CHUNK_SIZE: usize = 64 * BLOCK_SIZE;
+
+// openssl backed AES keyed with a KEY_SIZE byte key - 16, 24 or 32
+// The key schedule is done once in `new` and the crypters are reused for every block after that.
+pub struct OpensslAes<const KEY_SIZE: usize> {
+    encrypter: RefCell<Crypter>,
+    decrypter: RefCell<Crypter>,
+}
+
+impl<const KEY_SIZE: usize> OpensslAes<KEY_SIZE> {
+    pub fn new(key: &[u8]) -> Result<Self, CryptoError> {
+        let cipher = match Self::cipher() {
+            Some(cipher) if key.len() == KEY_SIZE => cipher,
+            _ => {
+                return Err(CryptoError::BadKeyLength {
+                    expected: KEY_SIZE,
+                    actual: key.len(),
+                })
+            }
+        };
+
+        Ok(OpensslAes {
+            encrypter: RefCell::new(Self::crypter(cipher, key, Mode::Encrypt)?),
+            decrypter: RefCell::new(Self::crypter(cipher, key, Mode::Decrypt)?),
+        })
+    }
+
+    // None if KEY_SIZE isn't an AES key size
+    fn cipher() -> Option<Cipher> {
+        match KEY_SIZE {
+            16 => Some(Cipher::aes_128_ecb()),
+            24 => Some(Cipher::aes_192_ecb()),
+            32 => Some(Cipher::aes_256_ecb()),
+            _ => None,
+        }
+    }
+
+    fn crypter(cipher: Cipher, key: &[u8], mode: Mode) -> Result<Crypter, CryptoError> {
+        // with padding off openssl hands back every full block from `update` straight away
+        // so the crypter never needs finalising and can be used for the lifetime of the key
+        let mut c = Crypter::new(cipher, mode, key, None)?;
+        c.pad(false);
+        Ok(c)
+    }
+
+    fn crypt_blocks(crypter: &RefCell<Crypter>, blocks: &mut [u8]) -> Result<(), CryptoError> {
+        assert_eq!(blocks.len() % BLOCK_SIZE, 0);
+
+        let mut crypter = crypter.borrow_mut();
+        let mut out = [0; CHUNK_SIZE + BLOCK_SIZE];
+        for chunk in blocks.chunks_mut(CHUNK_SIZE) {
+            let count = crypter.update(chunk, &mut out[..chunk.len() + BLOCK_SIZE])?;
+            assert_eq!(count, chunk.len());
+            chunk.copy_from_slice(&out[..count]);
+        }
+        Ok(())
+    }
+}
+
+impl<const KEY_SIZE: usize> BlockCipher for OpensslAes<KEY_SIZE> {
+    fn block_size(&self) -> usize {
+        BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) -> Result<(), CryptoError> {
+        assert_eq!(block.len(), BLOCK_SIZE);
+        Self::crypt_blocks(&self.encrypter, block)
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) -> Result<(), CryptoError> {
+        assert_eq!(block.len(), BLOCK_SIZE);
+        Self::crypt_blocks(&self.decrypter, block)
+    }
+
+    fn encrypt_blocks(&self, blocks: &mut [u8]) -> Result<(), CryptoError> {
+        Self::crypt_blocks(&self.encrypter, blocks)
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [u8]) -> Result<(), CryptoError> {
+        Self::crypt_blocks(&self.decrypter, blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aes_bulk_matches_single_blocks() {
+        let cipher = OpensslAes::<16>::new("YELLOW SUBMARINE".as_bytes()).unwrap();
+        let plain_text: Vec<u8> = (0..CHUNK_SIZE * 3 + 5 * BLOCK_SIZE)
+            .map(|i| i as u8)
+            .collect();
+
+        let mut bulk = plain_text.clone();
+        cipher.encrypt_blocks(&mut bulk).unwrap();
+
+        let mut single = plain_text.clone();
+        single
+            .chunks_exact_mut(BLOCK_SIZE)
+            .for_each(|block| cipher.encrypt_block(block).unwrap());
+        assert_eq!(bulk, single);
+
+        cipher.decrypt_blocks(&mut bulk).unwrap();
+        assert_eq!(bulk, plain_text);
+    }
+
+    #[test]
+    fn test_unsupported_key_size() {
+        assert_eq!(
+            OpensslAes::<20>::new(&[0; 20]).err(),
+            Some(CryptoError::BadKeyLength {
+                expected: 20,
+                actual: 20
+            })
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use crate::error::{CryptoError, PaddingError};
+use rand::{thread_rng, Rng};
+
 fn calc_padding(data: &[u8], block_size: usize) -> usize {
     block_size - (data.len() % block_size)
 }
@@ -10,48 +13,182 @@ pub fn pad(data: &mut Vec<u8>, block_size: usize) -> usize {
     padding
 }
 
-pub fn strip_padding(data: &mut Vec<u8>, block_size: usize) {
-    if data.len() % block_size != 0 {
-        return;
+pub fn strip_padding(data: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+    let unpadded_len = validate_padding(data, block_size)?.len();
+    data.truncate(unpadded_len);
+    Ok(())
+}
+
+pub fn validate_padding(data: &[u8], block_size: usize) -> Result<&[u8], CryptoError> {
+    let padding = last_byte(data, block_size)?;
+    let start = data.len() - check_length(padding, block_size)?;
+    check_bytes(data, start..data.len() - 1, padding)?;
+
+    Ok(&data[..start])
+}
+
+// A block cipher padding scheme. `pad` always leaves data a whole number of blocks
+// and `unpad` hands back the data with the padding removed, or why it couldn't.
+pub trait Padding {
+    fn pad(&self, data: &mut Vec<u8>, block_size: usize);
+
+    fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Result<&'a [u8], CryptoError>;
+
+    fn strip(&self, data: &mut Vec<u8>, block_size: usize) -> Result<(), CryptoError> {
+        let unpadded_len = self.unpad(data, block_size)?.len();
+        data.truncate(unpadded_len);
+        Ok(())
+    }
+}
+
+// n bytes of n
+pub struct Pkcs7;
+
+// n - 1 zero bytes then n
+pub struct AnsiX923;
+
+// n - 1 random bytes then n
+pub struct Iso10126;
+
+// 0x80 then zero bytes, i.e. a single 1 bit then 0 bits
+pub struct Iso7816;
+
+// zero bytes up to the block boundary, nothing if already aligned
+// ambiguous - unpadding also strips any zeros the data ended with
+pub struct ZeroPadding;
+
+// the final byte after checking the data is whole blocks
+fn last_byte(data: &[u8], block_size: usize) -> Result<u8, CryptoError> {
+    if !data.len().is_multiple_of(block_size) {
+        return Err(CryptoError::NotBlockAligned {
+            len: data.len(),
+            block_size,
+        });
+    }
+
+    Ok(*data.last().ok_or(PaddingError::Empty)?)
+}
+
+fn check_length(padding: u8, block_size: usize) -> Result<usize, CryptoError> {
+    if padding == 0 || padding as usize > block_size {
+        return Err(PaddingError::BadLength {
+            pad: padding,
+            block_size,
+        }
+        .into());
+    }
+    Ok(padding as usize)
+}
+
+fn check_bytes(
+    data: &[u8],
+    mut range: std::ops::Range<usize>,
+    expected: u8,
+) -> Result<(), CryptoError> {
+    match range.find(|&i| data[i] != expected) {
+        Some(index) => Err(PaddingError::BadByte {
+            index,
+            expected,
+            actual: data[index],
+        }
+        .into()),
+        None => Ok(()),
+    }
+}
+
+impl Padding for Pkcs7 {
+    fn pad(&self, data: &mut Vec<u8>, block_size: usize) {
+        pad(data, block_size);
     }
 
-    let padding = *data.last().unwrap();
-    if padding as usize > block_size {
-        return;
+    fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Result<&'a [u8], CryptoError> {
+        validate_padding(data, block_size)
     }
+}
+
+impl Padding for AnsiX923 {
+    fn pad(&self, data: &mut Vec<u8>, block_size: usize) {
+        let padding = calc_padding(data, block_size);
+        data.resize(data.len() + padding - 1, 0);
+        data.push(padding as u8);
+    }
+
+    fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Result<&'a [u8], CryptoError> {
+        let padding = last_byte(data, block_size)?;
+        let start = data.len() - check_length(padding, block_size)?;
+        check_bytes(data, start..data.len() - 1, 0)?;
 
-    if data
-        .iter()
-        .rev()
-        .take(padding as usize)
-        .cloned()
-        .all(|p| p == padding)
-    {
-        data.truncate(data.len() - padding as usize);
+        Ok(&data[..start])
     }
 }
 
-pub fn validate_padding(data: &[u8], block_size: usize) -> Result<&[u8], ()> {
-    if data.len() % block_size != 0 {
-        return Err(());
+impl Padding for Iso10126 {
+    fn pad(&self, data: &mut Vec<u8>, block_size: usize) {
+        let padding = calc_padding(data, block_size);
+        data.extend((1..padding).map(|_| thread_rng().gen::<u8>()));
+        data.push(padding as u8);
     }
 
-    let padding = *data.last().unwrap();
-    if padding as usize > block_size {
-        return Err(());
+    fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Result<&'a [u8], CryptoError> {
+        // the filler is random so only the length can be checked
+        let padding = last_byte(data, block_size)?;
+        let start = data.len() - check_length(padding, block_size)?;
+
+        Ok(&data[..start])
     }
+}
 
-    if !data
-        .iter()
-        .rev()
-        .take(padding as usize)
-        .cloned()
-        .all(|p| p == padding)
-    {
-        return Err(());
+impl Padding for Iso7816 {
+    fn pad(&self, data: &mut Vec<u8>, block_size: usize) {
+        let padding = calc_padding(data, block_size);
+        data.push(0x80);
+        data.resize(data.len() + padding - 1, 0);
     }
 
-    Ok(&data[0..data.len() - padding as usize])
+    fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Result<&'a [u8], CryptoError> {
+        last_byte(data, block_size)?;
+
+        // the marker has to be in the last block
+        let last_block = data.len() - block_size;
+        let marker = (last_block..data.len())
+            .rev()
+            .find(|&i| data[i] != 0)
+            .ok_or(PaddingError::MissingMarker)?;
+        if data[marker] != 0x80 {
+            return Err(PaddingError::BadByte {
+                index: marker,
+                expected: 0x80,
+                actual: data[marker],
+            }
+            .into());
+        }
+
+        Ok(&data[..marker])
+    }
+}
+
+impl Padding for ZeroPadding {
+    fn pad(&self, data: &mut Vec<u8>, block_size: usize) {
+        let aligned = data.len().div_ceil(block_size) * block_size;
+        data.resize(aligned, 0);
+    }
+
+    fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Result<&'a [u8], CryptoError> {
+        if !data.len().is_multiple_of(block_size) {
+            return Err(CryptoError::NotBlockAligned {
+                len: data.len(),
+                block_size,
+            });
+        }
+
+        let last_block = data.len().saturating_sub(block_size);
+        let end = (last_block..data.len())
+            .rev()
+            .find(|&i| data[i] != 0)
+            .map_or(last_block, |i| i + 1);
+
+        Ok(&data[..end])
+    }
 }
 
 #[cfg(test)]
@@ -92,4 +229,122 @@ mod tests {
             assert!(validate_padding(invalid.as_bytes(), 16).is_err());
         }
     }
+
+    #[test]
+    fn test_validate_padding_errors() {
+        assert_eq!(
+            validate_padding("ICE ICE BABY\x01\x02\x03\x04".as_bytes(), 16),
+            Err(CryptoError::InvalidPadding(PaddingError::BadByte {
+                index: 12,
+                expected: 4,
+                actual: 1
+            }))
+        );
+        assert_eq!(
+            validate_padding("ICE ICE BABY1234".as_bytes(), 16),
+            Err(CryptoError::InvalidPadding(PaddingError::BadLength {
+                pad: b'4',
+                block_size: 16
+            }))
+        );
+        assert_eq!(
+            validate_padding("ICE ICE BABY\x00".as_bytes(), 13),
+            Err(CryptoError::InvalidPadding(PaddingError::BadLength {
+                pad: 0,
+                block_size: 13
+            }))
+        );
+        assert_eq!(
+            validate_padding(&[], 16),
+            Err(CryptoError::InvalidPadding(PaddingError::Empty))
+        );
+        assert_eq!(
+            validate_padding("ICE ICE BABY\x04\x04\x04".as_bytes(), 16),
+            Err(CryptoError::NotBlockAligned {
+                len: 15,
+                block_size: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_strip_padding() {
+        let mut data = Vec::from("ICE ICE BABY\x04\x04\x04\x04".as_bytes());
+        assert_eq!(strip_padding(&mut data, 16), Ok(()));
+        assert_eq!(data, "ICE ICE BABY".as_bytes());
+
+        let mut data = Vec::from("ICE ICE BABY\x05\x05\x05\x05".as_bytes());
+        assert!(strip_padding(&mut data, 16).is_err());
+        assert_eq!(data.len(), 16);
+    }
+
+    #[test]
+    fn test_padding_schemes() {
+        let data = "YELLOW SUBMARINE".as_bytes();
+
+        let cases: [(&dyn Padding, &str); 4] = [
+            (&Pkcs7, "YELLOW SUBMARINE\x04\x04\x04\x04"),
+            (&AnsiX923, "YELLOW SUBMARINE\x00\x00\x00\x04"),
+            (&Iso7816, "YELLOW SUBMARINE\u{80}\x00\x00\x00"),
+            (&ZeroPadding, "YELLOW SUBMARINE\x00\x00\x00\x00"),
+        ];
+        for (padding, expected) in cases {
+            let expected: Vec<u8> = expected.chars().map(|c| c as u8).collect();
+
+            let mut padded = data.to_vec();
+            padding.pad(&mut padded, 20);
+            assert_eq!(padded, expected);
+            assert_eq!(padding.unpad(&padded, 20), Ok(data));
+        }
+
+        let mut padded = data.to_vec();
+        Iso10126.pad(&mut padded, 20);
+        assert_eq!(padded.len(), 20);
+        assert_eq!(padded[19], 4);
+        assert_eq!(Iso10126.unpad(&padded, 20), Ok(data));
+    }
+
+    #[test]
+    fn test_padding_schemes_full_block() {
+        let data = "YELLOW SUBMARINE".as_bytes();
+
+        for padding in [&Pkcs7 as &dyn Padding, &AnsiX923, &Iso10126, &Iso7816] {
+            let mut padded = data.to_vec();
+            padding.pad(&mut padded, 16);
+            assert_eq!(padded.len(), 32);
+            padding.strip(&mut padded, 16).unwrap();
+            assert_eq!(padded, data);
+        }
+
+        let mut padded = data.to_vec();
+        ZeroPadding.pad(&mut padded, 16);
+        assert_eq!(padded, data);
+    }
+
+    #[test]
+    fn test_invalid_padding_schemes() {
+        assert_eq!(
+            AnsiX923.unpad("ICE ICE BABY\x00\x01\x00\x04".as_bytes(), 16),
+            Err(CryptoError::InvalidPadding(PaddingError::BadByte {
+                index: 13,
+                expected: 0,
+                actual: 1
+            }))
+        );
+        assert_eq!(
+            Iso7816.unpad("ICE ICE BABY\x01\x00\x00\x00".as_bytes(), 16),
+            Err(CryptoError::InvalidPadding(PaddingError::BadByte {
+                index: 12,
+                expected: 0x80,
+                actual: 1
+            }))
+        );
+        assert_eq!(
+            Iso7816.unpad(&[0; 16], 16),
+            Err(CryptoError::InvalidPadding(PaddingError::MissingMarker))
+        );
+        assert!(Iso10126
+            .unpad("ICE ICE BABY\x00\x01\x00\x11".as_bytes(), 16)
+            .is_err());
+    }
 }
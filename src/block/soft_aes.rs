@@ -0,0 +1,370 @@
+use crate::block::aes::BLOCK_SIZE;
+use crate::block::cipher::BlockCipher;
+use crate::error::CryptoError;
+
+// Pure rust AES straight from FIPS-197.
+// Slow and not constant time - the point is that every step and every round is visible.
+//
+// The state is the 16 byte block in FIPS order, i.e. column major: byte i is row i % 4, column i / 4.
+
+pub type State = [u8; BLOCK_SIZE];
+
+pub const SBOX: [u8; 256] = make_sbox();
+pub const INV_SBOX: [u8; 256] = invert_sbox(&SBOX);
+
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+const fn xtime(b: u8) -> u8 {
+    (b << 1) ^ if b & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+// multiplication in GF(2^8) mod x^8 + x^4 + x^3 + x + 1
+pub const fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut result = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            result ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    result
+}
+
+const fn gf_inverse(b: u8) -> u8 {
+    // b^254 == b^-1, and conveniently 0 maps to 0
+    let mut result = 1;
+    let mut i = 0;
+    while i < 254 {
+        result = gf_mul(result, b);
+        i += 1;
+    }
+    result
+}
+
+const fn make_sbox() -> [u8; 256] {
+    let mut sbox = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let b = gf_inverse(i as u8);
+        sbox[i] =
+            b ^ b.rotate_left(1) ^ b.rotate_left(2) ^ b.rotate_left(3) ^ b.rotate_left(4) ^ 0x63;
+        i += 1;
+    }
+    sbox
+}
+
+const fn invert_sbox(sbox: &[u8; 256]) -> [u8; 256] {
+    let mut inverse = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        inverse[sbox[i] as usize] = i as u8;
+        i += 1;
+    }
+    inverse
+}
+
+pub fn sub_bytes(state: &mut State) {
+    state.iter_mut().for_each(|b| *b = SBOX[*b as usize]);
+}
+
+pub fn inv_sub_bytes(state: &mut State) {
+    state.iter_mut().for_each(|b| *b = INV_SBOX[*b as usize]);
+}
+
+// row r is rotated left by r
+pub fn shift_rows(state: &mut State) {
+    let copy = *state;
+    for col in 0..4 {
+        for row in 0..4 {
+            state[col * 4 + row] = copy[((col + row) % 4) * 4 + row];
+        }
+    }
+}
+
+pub fn inv_shift_rows(state: &mut State) {
+    let copy = *state;
+    for col in 0..4 {
+        for row in 0..4 {
+            state[((col + row) % 4) * 4 + row] = copy[col * 4 + row];
+        }
+    }
+}
+
+pub fn mix_columns(state: &mut State) {
+    for column in state.chunks_exact_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        // 2a ^ 3b ^ c ^ d == 2(a ^ b) ^ (a ^ b ^ c ^ d) ^ a
+        let all = a0 ^ a1 ^ a2 ^ a3;
+        column[0] = xtime(a0 ^ a1) ^ all ^ a0;
+        column[1] = xtime(a1 ^ a2) ^ all ^ a1;
+        column[2] = xtime(a2 ^ a3) ^ all ^ a2;
+        column[3] = xtime(a3 ^ a0) ^ all ^ a3;
+    }
+}
+
+pub fn inv_mix_columns(state: &mut State) {
+    for column in state.chunks_exact_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        column[0] = gf_mul(a0, 14) ^ gf_mul(a1, 11) ^ gf_mul(a2, 13) ^ gf_mul(a3, 9);
+        column[1] = gf_mul(a0, 9) ^ gf_mul(a1, 14) ^ gf_mul(a2, 11) ^ gf_mul(a3, 13);
+        column[2] = gf_mul(a0, 13) ^ gf_mul(a1, 9) ^ gf_mul(a2, 14) ^ gf_mul(a3, 11);
+        column[3] = gf_mul(a0, 11) ^ gf_mul(a1, 13) ^ gf_mul(a2, 9) ^ gf_mul(a3, 14);
+    }
+}
+
+pub fn add_round_key(state: &mut State, round_key: &State) {
+    state.iter_mut().zip(round_key).for_each(|(b, k)| *b ^= k);
+}
+
+// one round key per round plus the initial whitening key
+pub fn expand_key(key: &[u8]) -> Vec<State> {
+    assert!(
+        matches!(key.len(), 16 | 24 | 32),
+        "AES key must be 16, 24 or 32 bytes, got {}",
+        key.len()
+    );
+    let nk = key.len() / 4;
+    let rounds = nk + 6;
+
+    let mut words: Vec<[u8; 4]> = key
+        .chunks_exact(4)
+        .map(|w| [w[0], w[1], w[2], w[3]])
+        .collect();
+
+    for i in nk..4 * (rounds + 1) {
+        let mut temp = words[i - 1];
+        if i % nk == 0 {
+            temp.rotate_left(1);
+            temp.iter_mut().for_each(|b| *b = SBOX[*b as usize]);
+            temp[0] ^= RCON[i / nk - 1];
+        } else if nk > 6 && i % nk == 4 {
+            temp.iter_mut().for_each(|b| *b = SBOX[*b as usize]);
+        }
+        let prev = words[i - nk];
+        words.push([
+            prev[0] ^ temp[0],
+            prev[1] ^ temp[1],
+            prev[2] ^ temp[2],
+            prev[3] ^ temp[3],
+        ]);
+    }
+
+    words
+        .chunks_exact(4)
+        .map(|w| {
+            let mut round_key = [0; BLOCK_SIZE];
+            round_key.copy_from_slice(&w.concat());
+            round_key
+        })
+        .collect()
+}
+
+// AES keyed with a KEY_SIZE byte key - 16, 24 or 32
+pub struct SoftAes<const KEY_SIZE: usize> {
+    round_keys: Vec<State>,
+}
+
+impl<const KEY_SIZE: usize> SoftAes<KEY_SIZE> {
+    pub fn new(key: &[u8]) -> Result<Self, CryptoError> {
+        if key.len() != KEY_SIZE || !matches!(KEY_SIZE, 16 | 24 | 32) {
+            return Err(CryptoError::BadKeyLength {
+                expected: KEY_SIZE,
+                actual: key.len(),
+            });
+        }
+
+        Ok(SoftAes {
+            round_keys: expand_key(key),
+        })
+    }
+
+    // 10, 12 or 14
+    pub fn rounds(&self) -> usize {
+        self.round_keys.len() - 1
+    }
+
+    pub fn round_keys(&self) -> &[State] {
+        &self.round_keys
+    }
+
+    // Encrypt with only the first `rounds` rounds, the last of which skips MixColumns like the real final round.
+    // `encrypt_rounds(state, self.rounds())` is plain AES.
+    pub fn encrypt_rounds(&self, state: &mut State, rounds: usize) {
+        self.encrypt_traced(state, rounds, |_, _| {});
+    }
+
+    pub fn decrypt_rounds(&self, state: &mut State, rounds: usize) {
+        assert!(rounds >= 1 && rounds <= self.rounds());
+
+        add_round_key(state, &self.round_keys[rounds]);
+        inv_shift_rows(state);
+        inv_sub_bytes(state);
+        for round in (1..rounds).rev() {
+            add_round_key(state, &self.round_keys[round]);
+            inv_mix_columns(state);
+            inv_shift_rows(state);
+            inv_sub_bytes(state);
+        }
+        add_round_key(state, &self.round_keys[0]);
+    }
+
+    // As `encrypt_rounds` but calls `observe(round, state)` with the state at the end of every round.
+    // Round 0 is the state after the initial AddRoundKey.
+    pub fn encrypt_traced<F: FnMut(usize, &State)>(
+        &self,
+        state: &mut State,
+        rounds: usize,
+        mut observe: F,
+    ) {
+        assert!(rounds >= 1 && rounds <= self.rounds());
+
+        add_round_key(state, &self.round_keys[0]);
+        observe(0, state);
+        for round in 1..rounds {
+            sub_bytes(state);
+            shift_rows(state);
+            mix_columns(state);
+            add_round_key(state, &self.round_keys[round]);
+            observe(round, state);
+        }
+        sub_bytes(state);
+        shift_rows(state);
+        add_round_key(state, &self.round_keys[rounds]);
+        observe(rounds, state);
+    }
+
+    // every intermediate state of a full encryption, see `encrypt_traced`
+    pub fn trace(&self, block: &State) -> Vec<State> {
+        let mut states = Vec::with_capacity(self.rounds() + 1);
+        let mut state = *block;
+        self.encrypt_traced(&mut state, self.rounds(), |_, s| states.push(*s));
+        states
+    }
+}
+
+impl<const KEY_SIZE: usize> BlockCipher for SoftAes<KEY_SIZE> {
+    fn block_size(&self) -> usize {
+        BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) -> Result<(), CryptoError> {
+        let state: &mut State = block.try_into().unwrap();
+        self.encrypt_rounds(state, self.rounds());
+        Ok(())
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) -> Result<(), CryptoError> {
+        let state: &mut State = block.try_into().unwrap();
+        self.decrypt_rounds(state, self.rounds());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(hex_str: &str) -> State {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_sbox() {
+        assert_eq!(SBOX[0x00], 0x63);
+        assert_eq!(SBOX[0x53], 0xed);
+        assert_eq!(SBOX[0xff], 0x16);
+        assert_eq!(INV_SBOX[0x63], 0x00);
+        assert_eq!(INV_SBOX[0xed], 0x53);
+    }
+
+    #[test]
+    fn test_gf_mul() {
+        // FIPS-197 4.2
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        assert_eq!(gf_mul(0x57, 0x13), 0xfe);
+    }
+
+    #[test]
+    fn test_steps_invert() {
+        let original = state("00112233445566778899aabbccddeeff");
+        let mut s = original;
+
+        shift_rows(&mut s);
+        assert_eq!(s, state("0055aaff4499ee3388dd2277cc1166bb"));
+        inv_shift_rows(&mut s);
+        assert_eq!(s, original);
+
+        mix_columns(&mut s);
+        inv_mix_columns(&mut s);
+        assert_eq!(s, original);
+
+        sub_bytes(&mut s);
+        inv_sub_bytes(&mut s);
+        assert_eq!(s, original);
+    }
+
+    #[test]
+    fn test_expand_key() {
+        // FIPS-197 appendix A
+        let round_keys = expand_key(&hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap());
+        assert_eq!(round_keys.len(), 11);
+        assert_eq!(round_keys[1], state("a0fafe1788542cb123a339392a6c7605"));
+        assert_eq!(round_keys[10], state("d014f9a8c9ee2589e13f0cc8b6630ca6"));
+
+        let round_keys = expand_key(
+            &hex::decode("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+                .unwrap(),
+        );
+        assert_eq!(round_keys.len(), 15);
+        assert_eq!(round_keys[14], state("fe4890d1e6188d0b046df344706c631e"));
+    }
+
+    #[test]
+    fn test_trace() {
+        // FIPS-197 appendix C.1
+        let aes =
+            SoftAes::<16>::new(&hex::decode("000102030405060708090a0b0c0d0e0f").unwrap()).unwrap();
+        let states = aes.trace(&state("00112233445566778899aabbccddeeff"));
+
+        assert_eq!(aes.rounds(), 10);
+        assert_eq!(states.len(), 11);
+        assert_eq!(states[0], state("00102030405060708090a0b0c0d0e0f0"));
+        assert_eq!(states[1], state("89d810e8855ace682d1843d8cb128fe4"));
+        assert_eq!(states[10], state("69c4e0d86a7b0430d8cdb78070b4c55a"));
+    }
+
+    #[test]
+    fn test_reduced_rounds_invert() {
+        let aes = SoftAes::<24>::new(&[7; 24]).unwrap();
+        let original = state("00112233445566778899aabbccddeeff");
+
+        for rounds in 1..=aes.rounds() {
+            let mut s = original;
+            aes.encrypt_rounds(&mut s, rounds);
+            assert_ne!(s, original);
+            aes.decrypt_rounds(&mut s, rounds);
+            assert_eq!(s, original);
+        }
+    }
+
+    #[cfg(feature = "openssl")]
+    #[test]
+    fn test_matches_openssl() {
+        use crate::block::openssl_aes::OpensslAes;
+
+        let key: Vec<u8> = (0..32).map(|i| i * 7).collect();
+        let mut soft: Vec<u8> = (0..256).map(|i| i as u8).collect();
+        let mut openssl = soft.clone();
+
+        SoftAes::<32>::new(&key)
+            .unwrap()
+            .encrypt_blocks(&mut soft)
+            .unwrap();
+        OpensslAes::<32>::new(&key)
+            .unwrap()
+            .encrypt_blocks(&mut openssl)
+            .unwrap();
+        assert_eq!(soft, openssl);
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    BadKeyLength { expected: usize, actual: usize },
+    BadIvLength { expected: usize, actual: usize },
+    BadNonceLength { expected: usize, actual: usize },
+    NotBlockAligned { len: usize, block_size: usize },
+    ZeroBlockSize,
+    // the block can't hold what has to go in it, e.g. a CTR counter
+    BlockTooSmall { block_size: usize, needed: usize },
+    InvalidPadding(PaddingError),
+    // a padding oracle accepted none of the 256 guesses for this byte of the block
+    OracleFailed { index: usize },
+    // decrypted fine but isn't ascii - carries the offending plain text, like a careless service would
+    NotAscii { plain_text: Vec<u8> },
+    Backend(String),
+}
+
+// what exactly was wrong with the padding - kept separate so padding failures can be matched on as a group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaddingError {
+    Empty,
+    MissingMarker,
+    BadLength {
+        pad: u8,
+        block_size: usize,
+    },
+    BadByte {
+        index: usize,
+        expected: u8,
+        actual: u8,
+    },
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::BadKeyLength { expected, actual } => {
+                write!(f, "bad key length: expected {expected} bytes, got {actual}")
+            }
+            CryptoError::BadIvLength { expected, actual } => {
+                write!(f, "bad IV length: expected {expected} bytes, got {actual}")
+            }
+            CryptoError::BadNonceLength { expected, actual } => {
+                write!(
+                    f,
+                    "bad nonce length: expected {expected} bytes, got {actual}"
+                )
+            }
+            CryptoError::NotBlockAligned { len, block_size } => {
+                write!(
+                    f,
+                    "{len} bytes is not a multiple of the {block_size} byte block size"
+                )
+            }
+            CryptoError::ZeroBlockSize => write!(f, "block size can't be zero"),
+            CryptoError::BlockTooSmall { block_size, needed } => {
+                write!(
+                    f,
+                    "{block_size} byte blocks are too small, need at least {needed}"
+                )
+            }
+            CryptoError::InvalidPadding(detail) => write!(f, "invalid padding: {detail}"),
+            CryptoError::OracleFailed { index } => {
+                write!(f, "padding oracle rejected every guess for byte {index}")
+            }
+            CryptoError::NotAscii { plain_text } => {
+                write!(
+                    f,
+                    "plain text is not ascii: {}",
+                    String::from_utf8_lossy(plain_text)
+                )
+            }
+            CryptoError::Backend(msg) => write!(f, "cipher backend failure: {msg}"),
+        }
+    }
+}
+
+impl fmt::Display for PaddingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaddingError::Empty => write!(f, "no data to unpad"),
+            PaddingError::MissingMarker => write!(f, "no padding marker in the final block"),
+            PaddingError::BadLength { pad, block_size } => {
+                write!(f, "pad length {pad} is not in 1..={block_size}")
+            }
+            PaddingError::BadByte {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "byte {index} should be {expected:#04x} but is {actual:#04x}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+impl From<PaddingError> for CryptoError {
+    fn from(e: PaddingError) -> Self {
+        CryptoError::InvalidPadding(e)
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl From<openssl::error::ErrorStack> for CryptoError {
+    fn from(e: openssl::error::ErrorStack) -> Self {
+        CryptoError::Backend(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            CryptoError::from(PaddingError::BadByte {
+                index: 14,
+                expected: 2,
+                actual: 3
+            })
+            .to_string(),
+            "invalid padding: byte 14 should be 0x02 but is 0x03"
+        );
+        assert_eq!(
+            CryptoError::NotBlockAligned {
+                len: 17,
+                block_size: 16
+            }
+            .to_string(),
+            "17 bytes is not a multiple of the 16 byte block size"
+        );
+        assert_eq!(
+            CryptoError::OracleFailed { index: 15 }.to_string(),
+            "padding oracle rejected every guess for byte 15"
+        );
+        assert_eq!(
+            CryptoError::NotAscii {
+                plain_text: b"caf\xc3\xa9".to_vec()
+            }
+            .to_string(),
+            "plain text is not ascii: caf\u{e9}"
+        );
+    }
+}
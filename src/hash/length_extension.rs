@@ -0,0 +1,136 @@
+use crate::hash::merkle_damgard::{Hasher, MerkleDamgard};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forgery {
+    pub key_len: usize,
+    // original || glue || extension - what to send along with the tag
+    pub message: Vec<u8>,
+    pub tag: Vec<u8>,
+}
+
+// Given tag = H(key || original) and a guess at the key length, the tag for original || glue || extension.
+// The glue is the padding H put after key || original, after it the hash state is just the tag.
+// None if the tag isn't an H digest.
+pub fn forge<H: MerkleDamgard>(
+    tag: &[u8],
+    original: &[u8],
+    extension: &[u8],
+    key_len: usize,
+) -> Option<Forgery> {
+    let prefix_len = (key_len + original.len()) as u64;
+    let glue = H::padding(prefix_len);
+
+    let mut hasher =
+        Hasher::<H>::from_state(H::state_from_digest(tag)?, prefix_len + glue.len() as u64);
+    hasher.update(extension);
+
+    let mut message = original.to_vec();
+    message.extend(glue);
+    message.extend_from_slice(extension);
+
+    Some(Forgery {
+        key_len,
+        message,
+        tag: hasher.finalize(),
+    })
+}
+
+// Try each key length until `verify` (usually the service checking a MAC) accepts the forgery.
+pub fn length_extension<H, I, F>(
+    tag: &[u8],
+    original: &[u8],
+    extension: &[u8],
+    key_lens: I,
+    verify: F,
+) -> Option<Forgery>
+where
+    H: MerkleDamgard,
+    I: IntoIterator<Item = usize>,
+    F: Fn(&[u8], &[u8]) -> bool,
+{
+    key_lens
+        .into_iter()
+        .map_while(|key_len| forge::<H>(tag, original, extension, key_len))
+        .find(|forgery| verify(&forgery.message, &forgery.tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::merkle_damgard::secret_prefix_mac;
+    use crate::hash::{Md4, Md5, Sha1, Sha256};
+    use rand::{thread_rng, Rng};
+
+    const ORIGINAL: &[u8] =
+        b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon";
+
+    fn random_key() -> Vec<u8> {
+        let mut rng = thread_rng();
+        (0..rng.gen_range(1..33)).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn test_forge_sha1_known_key_len() {
+        let key = b"YELLOW SUBMARINE";
+        let tag = secret_prefix_mac::<Sha1>(key, ORIGINAL);
+
+        let forgery = forge::<Sha1>(&tag, ORIGINAL, b";admin=true", key.len()).unwrap();
+        assert!(forgery.message.starts_with(ORIGINAL));
+        assert!(forgery.message.ends_with(b";admin=true"));
+        assert_eq!(
+            forgery.tag,
+            secret_prefix_mac::<Sha1>(key, &forgery.message)
+        );
+
+        assert_eq!(forge::<Sha1>(&tag[..16], ORIGINAL, b"", key.len()), None);
+    }
+
+    #[test]
+    fn test_sha1_length_extension() {
+        let key = random_key();
+        let tag = secret_prefix_mac::<Sha1>(&key, ORIGINAL);
+
+        let forgery =
+            length_extension::<Sha1, _, _>(&tag, ORIGINAL, b";admin=true", 0..64, |m, t| {
+                secret_prefix_mac::<Sha1>(&key, m) == t
+            })
+            .unwrap();
+
+        assert_eq!(forgery.key_len, key.len());
+        assert!(forgery.message.ends_with(b";admin=true"));
+    }
+
+    #[test]
+    fn test_sha1_length_extension_key_out_of_range() {
+        let key = [0x42; 40];
+        let tag = secret_prefix_mac::<Sha1>(&key, ORIGINAL);
+
+        assert_eq!(
+            length_extension::<Sha1, _, _>(&tag, ORIGINAL, b";admin=true", 0..32, |m, t| {
+                secret_prefix_mac::<Sha1>(&key, m) == t
+            }),
+            None
+        );
+    }
+
+    fn check_length_extension<H: MerkleDamgard>() {
+        let key = random_key();
+        let tag = secret_prefix_mac::<H>(&key, ORIGINAL);
+
+        let forgery = length_extension::<H, _, _>(&tag, ORIGINAL, b";admin=true", 0..64, |m, t| {
+            secret_prefix_mac::<H>(&key, m) == t
+        })
+        .unwrap();
+
+        assert_eq!(forgery.key_len, key.len());
+        assert!(forgery.message.ends_with(b";admin=true"));
+    }
+
+    #[test]
+    fn test_length_extension_every_hash() {
+        check_length_extension::<Md4>();
+        check_length_extension::<Md5>();
+        check_length_extension::<Sha1>();
+        check_length_extension::<Sha256>();
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+// byte order of the message words, the length at the end of the padding and the digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+// A Merkle-Damgard hash is its compression function plus how it pads - everything else
+// (buffering, padding, turning state into a digest and back) is shared. Implemented by unit structs, see `Hasher`.
+pub trait MerkleDamgard {
+    type State: Copy + Debug + AsRef<[u32]> + AsMut<[u32]>;
+
+    const INITIAL_STATE: Self::State;
+    const BLOCK_SIZE: usize = 64;
+    const ENDIAN: Endian;
+
+    fn compress(state: &mut Self::State, block: &[u8]);
+
+    // 0x80, zeros up to 8 bytes short of a block, then the bit length of a `message_len` byte message
+    fn padding(message_len: u64) -> Vec<u8> {
+        let block_size = Self::BLOCK_SIZE;
+        let zeros = (block_size * 2 - 9 - (message_len as usize % block_size)) % block_size;
+
+        let bits = message_len.wrapping_mul(8);
+        let mut glue = vec![0x80];
+        glue.resize(1 + zeros, 0);
+        match Self::ENDIAN {
+            Endian::Little => glue.extend_from_slice(&bits.to_le_bytes()),
+            Endian::Big => glue.extend_from_slice(&bits.to_be_bytes()),
+        }
+        glue
+    }
+
+    fn digest_size() -> usize {
+        Self::INITIAL_STATE.as_ref().len() * 4
+    }
+
+    fn state_to_digest(state: &Self::State) -> Vec<u8> {
+        state
+            .as_ref()
+            .iter()
+            .flat_map(|s| match Self::ENDIAN {
+                Endian::Little => s.to_le_bytes(),
+                Endian::Big => s.to_be_bytes(),
+            })
+            .collect()
+    }
+
+    // the chaining state a digest came from, None if it's the wrong size
+    fn state_from_digest(digest: &[u8]) -> Option<Self::State> {
+        if digest.len() != Self::digest_size() {
+            return None;
+        }
+
+        let mut state = Self::INITIAL_STATE;
+        for (s, word) in state.as_mut().iter_mut().zip(digest.chunks_exact(4)) {
+            *s = read_word(word, Self::ENDIAN);
+        }
+        Some(state)
+    }
+
+    fn digest(data: &[u8]) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut hasher = Hasher::<Self>::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+pub(crate) fn read_word(bytes: &[u8], endian: Endian) -> u32 {
+    let bytes = bytes.try_into().unwrap();
+    match endian {
+        Endian::Little => u32::from_le_bytes(bytes),
+        Endian::Big => u32::from_be_bytes(bytes),
+    }
+}
+
+// message words of a block for the compression functions
+pub(crate) fn read_block<const WORDS: usize>(block: &[u8], endian: Endian) -> [u32; WORDS] {
+    let mut words = [0u32; WORDS];
+    for (w, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *w = read_word(bytes, endian);
+    }
+    words
+}
+
+// Streaming hasher with the chaining state and length open, so it can pick up from someone else's digest.
+#[derive(Debug, Clone)]
+pub struct Hasher<H: MerkleDamgard> {
+    state: H::State,
+    // total bytes hashed so far, including any already in `state`
+    message_len: u64,
+    buffer: Vec<u8>,
+    hash: PhantomData<H>,
+}
+
+impl<H: MerkleDamgard> Default for Hasher<H> {
+    fn default() -> Self {
+        Hasher::from_state(H::INITIAL_STATE, 0)
+    }
+}
+
+impl<H: MerkleDamgard> Hasher<H> {
+    pub fn new() -> Self {
+        Hasher::default()
+    }
+
+    // carry on from `state` as if `message_len` bytes (a whole number of blocks, padding included) had been hashed
+    pub fn from_state(state: H::State, message_len: u64) -> Self {
+        assert!(message_len.is_multiple_of(H::BLOCK_SIZE as u64));
+        Hasher {
+            state,
+            message_len,
+            buffer: Vec::with_capacity(H::BLOCK_SIZE),
+            hash: PhantomData,
+        }
+    }
+
+    pub fn state(&self) -> H::State {
+        self.state
+    }
+
+    pub fn message_len(&self) -> u64 {
+        self.message_len
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.message_len += data.len() as u64;
+
+        if !self.buffer.is_empty() {
+            let take = (H::BLOCK_SIZE - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.buffer.len() < H::BLOCK_SIZE {
+                return;
+            }
+            let block = std::mem::take(&mut self.buffer);
+            H::compress(&mut self.state, &block);
+        }
+
+        let mut blocks = data.chunks_exact(H::BLOCK_SIZE);
+        for block in blocks.by_ref() {
+            H::compress(&mut self.state, block);
+        }
+        self.buffer.extend_from_slice(blocks.remainder());
+    }
+
+    pub fn finalize(mut self) -> Vec<u8> {
+        let glue = H::padding(self.message_len);
+        self.update(&glue);
+        debug_assert!(self.buffer.is_empty());
+
+        H::state_to_digest(&self.state)
+    }
+}
+
+// H(key || message) - the naive MAC that length extension breaks
+pub fn secret_prefix_mac<H: MerkleDamgard>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut hasher = Hasher::<H>::new();
+    hasher.update(key);
+    hasher.update(message);
+    hasher.finalize()
+}
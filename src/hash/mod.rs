@@ -0,0 +1,13 @@
+mod length_extension;
+mod md4;
+mod md5;
+mod merkle_damgard;
+mod sha1;
+mod sha256;
+
+pub use self::length_extension::{forge, length_extension, Forgery};
+pub use self::md4::{md4, Md4};
+pub use self::md5::{md5, Md5};
+pub use self::merkle_damgard::{secret_prefix_mac, Endian, Hasher, MerkleDamgard};
+pub use self::sha1::{sha1, Sha1};
+pub use self::sha256::{sha256, Sha256};
@@ -0,0 +1,129 @@
+use crate::hash::merkle_damgard::{read_block, Endian, MerkleDamgard};
+
+// SHA-1 from FIPS 180-4
+#[derive(Debug, Clone, Copy)]
+pub struct Sha1;
+
+impl MerkleDamgard for Sha1 {
+    type State = [u32; 5];
+
+    const INITIAL_STATE: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    const ENDIAN: Endian = Endian::Big;
+
+    fn compress(state: &mut [u32; 5], block: &[u8]) {
+        let mut w = [0u32; 80];
+        w[..16].copy_from_slice(&read_block::<16>(block, Endian::Big));
+        for t in 16..80 {
+            w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = *state;
+        for (t, w) in w.iter().enumerate() {
+            let (f, k) = match t {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*w);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+}
+
+pub fn sha1(data: &[u8]) -> Vec<u8> {
+    Sha1::digest(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::merkle_damgard::{secret_prefix_mac, Hasher};
+
+    #[test]
+    fn test_sha1_vectors() {
+        let vectors = [
+            ("", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            ("abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            ),
+            (
+                "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+                "a49b2446a02c645bf419f995b67091253a04a259",
+            ),
+            (
+                "The quick brown fox jumps over the lazy dog",
+                "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12",
+            ),
+        ];
+
+        for (input, expected) in vectors {
+            assert_eq!(hex::encode(sha1(input.as_bytes())), expected);
+        }
+    }
+
+    #[test]
+    fn test_sha1_million_a() {
+        let mut hasher = Hasher::<Sha1>::new();
+        // odd sized chunks so the buffering gets exercised
+        for chunk in vec![b'a'; 1_000_000].chunks(999) {
+            hasher.update(chunk);
+        }
+        assert_eq!(
+            hex::encode(hasher.finalize()),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+    }
+
+    #[test]
+    fn test_padding() {
+        for len in 0..200u64 {
+            let glue = Sha1::padding(len);
+            assert_eq!((len + glue.len() as u64) % 64, 0);
+            assert_eq!(glue[0], 0x80);
+            assert_eq!(glue[glue.len() - 8..], (len * 8).to_be_bytes());
+        }
+        assert_eq!(Sha1::padding(55).len(), 9);
+        assert_eq!(Sha1::padding(56).len(), 72);
+    }
+
+    #[test]
+    fn test_from_state() {
+        // hashing a padded first block then resuming from its digest is the same as hashing it all at once
+        let first = b"the first part";
+        let digest = sha1(first);
+
+        let mut resumed = Hasher::<Sha1>::from_state(Sha1::state_from_digest(&digest).unwrap(), 64);
+        resumed.update(b"more");
+
+        let mut glued = first.to_vec();
+        glued.extend(Sha1::padding(first.len() as u64));
+        glued.extend(b"more");
+
+        assert_eq!(resumed.finalize(), sha1(&glued));
+        assert_eq!(Sha1::state_from_digest(&digest[1..]), None);
+    }
+
+    #[test]
+    fn test_secret_prefix_mac() {
+        let mac = secret_prefix_mac::<Sha1>(b"key", b"message");
+        assert_eq!(mac, sha1(b"keymessage"));
+        assert_ne!(mac, secret_prefix_mac::<Sha1>(b"kez", b"message"));
+        assert_ne!(mac, secret_prefix_mac::<Sha1>(b"key", b"messagf"));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::hash::{Hasher, MerkleDamgard};
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+// RFC 2104 - H((K ^ opad) || H((K ^ ipad) || message)), keys longer than a block are hashed first
+pub fn hmac<H: MerkleDamgard>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut block_key = if key.len() > H::BLOCK_SIZE {
+        H::digest(key)
+    } else {
+        key.to_vec()
+    };
+    block_key.resize(H::BLOCK_SIZE, 0);
+
+    let padded_key = |pad: u8| -> Vec<u8> { block_key.iter().map(|k| k ^ pad).collect() };
+
+    let mut inner = Hasher::<H>::new();
+    inner.update(&padded_key(IPAD));
+    inner.update(message);
+
+    let mut outer = Hasher::<H>::new();
+    outer.update(&padded_key(OPAD));
+    outer.update(&inner.finalize());
+    outer.finalize()
+}
+
+// Compare tags without giving away where they differ - every byte gets looked at whatever happens.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// How not to compare tags - stops at the first wrong byte, so the time taken says how many were right.
+pub fn early_exit_eq(a: &[u8], b: &[u8]) -> bool {
+    early_exit_eq_with(a, b, || {})
+}
+
+// `early_exit_eq` calling `on_match` after every byte that matches, for slowing the leak down enough to measure
+pub(crate) fn early_exit_eq_with<F: FnMut()>(a: &[u8], b: &[u8], mut on_match: F) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    for (x, y) in a.iter().zip(b) {
+        if x != y {
+            return false;
+        }
+        on_match();
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::{Md5, Sha1, Sha256};
+
+    // (key, data) for RFC 2202 test cases 1-7
+    fn rfc_2202_inputs() -> Vec<(Vec<u8>, Vec<u8>)> {
+        vec![
+            (vec![0x0b; 20], b"Hi There".to_vec()),
+            (b"Jefe".to_vec(), b"what do ya want for nothing?".to_vec()),
+            (vec![0xaa; 20], vec![0xdd; 50]),
+            ((1..=25).collect(), vec![0xcd; 50]),
+            (vec![0x0c; 20], b"Test With Truncation".to_vec()),
+            (
+                vec![0xaa; 80],
+                b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+            ),
+            (
+                vec![0xaa; 80],
+                b"Test Using Larger Than Block-Size Key and Larger Than One Block-Size Data"
+                    .to_vec(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_hmac_sha1_rfc_2202() {
+        let expected = [
+            "b617318655057264e28bc0b6fb378c8ef146be00",
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+            "125d7342b9ac11cd91a39af48aa17b4f63f175d3",
+            "4c9007f4026250c6bc8414f9bf50c86c2d7235da",
+            "4c1a03424b55e07fe7f27be1d58bb9324a9a5a04",
+            "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+            "e8e99d0f45237d786d6bbaa7965c7808bbff1a91",
+        ];
+
+        for ((key, data), expected) in rfc_2202_inputs().iter().zip(expected) {
+            assert_eq!(hex::encode(hmac::<Sha1>(key, data)), expected);
+        }
+    }
+
+    #[test]
+    fn test_hmac_md5_rfc_2202() {
+        let mut inputs = rfc_2202_inputs();
+        // the MD5 cases use 16 byte keys where SHA-1 uses 20
+        inputs[0].0 = vec![0x0b; 16];
+        inputs[2].0 = vec![0xaa; 16];
+        inputs[4].0 = vec![0x0c; 16];
+
+        let expected = [
+            "9294727a3638bb1c13f48ef8158bfc9d",
+            "750c783e6ab0b503eaa86e310a5db738",
+            "56be34521d144c88dbb8c733f0e8b3f6",
+            "697eaf0aca3a3aea3a75164746ffaa79",
+            "56461ef2342edc00f9bab995690efd4c",
+            "6b1ab7fe4bd7bf8f0b62e6ce61b9d0cd",
+            "6f630fad67cda0ee1fb1f562db3aa53e",
+        ];
+
+        for ((key, data), expected) in inputs.iter().zip(expected) {
+            assert_eq!(hex::encode(hmac::<Md5>(key, data)), expected);
+        }
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc_4231() {
+        // test cases 1-4, 6 and 7 - 5 is about truncating the output
+        let cases: [(Vec<u8>, &[u8], &str); 6] = [
+            (
+                vec![0x0b; 20],
+                b"Hi There",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                vec![0xaa; 20],
+                &[0xdd; 50],
+                "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+            ),
+            (
+                (1..=25).collect(),
+                &[0xcd; 50],
+                "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+            ),
+            (
+                vec![0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+            (
+                vec![0xaa; 131],
+                b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+                "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+            ),
+        ];
+
+        for (key, data, expected) in cases {
+            assert_eq!(hex::encode(hmac::<Sha256>(&key, data)), expected);
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let tag = hmac::<Sha1>(b"key", b"message");
+        let mut wrong = tag.clone();
+        wrong[19] ^= 1;
+
+        for eq in [constant_time_eq, early_exit_eq] {
+            assert!(eq(&tag, &tag));
+            assert!(!eq(&tag, &wrong));
+            assert!(!eq(&tag, &tag[..19]));
+            assert!(eq(&[], &[]));
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use crate::timing;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+// Ask the server whether `signature` is right for `file`, and how long it took to answer.
+pub fn send_signed_request(
+    addr: SocketAddr,
+    file: &str,
+    signature: &[u8],
+) -> io::Result<(bool, Duration)> {
+    let mut stream = TcpStream::connect(addr)?;
+    stream.set_nodelay(true)?;
+    let request = format!(
+        "GET /test?file={}&signature={} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n",
+        percent_encode(file),
+        hex::encode(signature)
+    );
+
+    let start = Instant::now();
+    stream.write_all(request.as_bytes())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let elapsed = start.elapsed();
+
+    Ok((response.starts_with("HTTP/1.1 200"), elapsed))
+}
+
+// %XX escape everything but the unreserved characters so the file name can't break out of its query parameter
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+// how many of the slowest candidates get sampled again to confirm a winner
+const CONFIRM_CANDIDATES: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct TimingAttackConfig {
+    // requests per candidate byte each round
+    pub samples: usize,
+    // fraction cut off each end before averaging, to lose the requests the scheduler got in the way of
+    pub trim: f64,
+    // how sure `timing::rank` has to be that the slowest candidate is slower than the runner up
+    pub confidence: f64,
+    // rounds of sampling before giving up on being sure and going with the slowest anyway
+    pub max_rounds: usize,
+}
+
+impl Default for TimingAttackConfig {
+    fn default() -> Self {
+        TimingAttackConfig {
+            samples: 5,
+            trim: 0.2,
+            confidence: 0.999,
+            max_rounds: 10,
+        }
+    }
+}
+
+// Recover the `tag_len` byte signature for `file` one byte at a time: with everything before it right, the right
+// guess for the next byte gets one more sleep out of the server's compare than the wrong ones.
+// The last byte doesn't need timing, the server just says yes to it. Ok(None) if no last byte worked -
+// an earlier byte was guessed wrong.
+pub fn recover_signature(
+    addr: SocketAddr,
+    file: &str,
+    tag_len: usize,
+    config: &TimingAttackConfig,
+) -> io::Result<Option<Vec<u8>>> {
+    recover_signature_with(
+        |signature| send_signed_request(addr, file, signature),
+        tag_len,
+        config,
+    )
+}
+
+// `recover_signature` against any `request` that says whether a signature is right and how long that took to find out
+pub fn recover_signature_with<F: FnMut(&[u8]) -> io::Result<(bool, Duration)>>(
+    mut request: F,
+    tag_len: usize,
+    config: &TimingAttackConfig,
+) -> io::Result<Option<Vec<u8>>> {
+    if tag_len == 0 {
+        return Ok(None);
+    }
+    // no samples or no rounds to take them in means nothing for `timing::rank` to go on
+    if config.samples == 0 || config.max_rounds == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "need at least one round and one sample per candidate",
+        ));
+    }
+    let mut signature = vec![0u8; tag_len];
+
+    for i in 0..tag_len - 1 {
+        signature[i] = slowest_byte(&mut request, &mut signature, i, config)?;
+    }
+
+    let last = tag_len - 1;
+    for guess in 0u8..=255 {
+        signature[last] = guess;
+        if request(&signature)?.0 {
+            return Ok(Some(signature));
+        }
+    }
+    Ok(None)
+}
+
+fn slowest_byte<F: FnMut(&[u8]) -> io::Result<(bool, Duration)>>(
+    request: &mut F,
+    signature: &mut [u8],
+    i: usize,
+    config: &TimingAttackConfig,
+) -> io::Result<u8> {
+    let mut samples = vec![Vec::new(); 256];
+    let mut sample = |samples: &mut Vec<Vec<f64>>, guesses: &[u8]| -> io::Result<()> {
+        for &guess in guesses {
+            signature[i] = guess;
+            for _s in 0..config.samples {
+                let elapsed = request(signature)?.1;
+                samples[guess as usize].push(elapsed.as_secs_f64());
+            }
+        }
+        Ok(())
+    };
+    let ranked = |samples: &[Vec<f64>]| {
+        timing::rank(
+            (0..=255u8).zip(samples.iter().cloned()).collect(),
+            config.trim,
+        )
+    };
+
+    let everything: Vec<u8> = (0..=255).collect();
+    for _round in 0..config.max_rounds {
+        sample(&mut samples, &everything)?;
+
+        let first = ranked(&samples);
+        if first[0].confidence < config.confidence {
+            continue;
+        }
+
+        // with 255 wrong guesses one of them will have been unlucky now and then - before believing it
+        // give the front runners another go and make sure the same one is still clear
+        let front_runners: Vec<u8> = first
+            .iter()
+            .take(CONFIRM_CANDIDATES)
+            .map(|r| r.candidate)
+            .collect();
+        sample(&mut samples, &front_runners)?;
+
+        let confirmed = ranked(&samples);
+        if confirmed[0].candidate == first[0].candidate
+            && confirmed[0].confidence >= config.confidence
+        {
+            return Ok(confirmed[0].candidate);
+        }
+    }
+
+    Ok(ranked(&samples)[0].candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha1;
+    use crate::hmac::hmac;
+    use crate::hmac_timing::server::{HmacServer, ServerConfig};
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("foo.txt"), "foo.txt");
+        assert_eq!(percent_encode("a b&c=d"), "a%20b%26c%3Dd");
+        assert_eq!(percent_encode("caf\u{e9}"), "caf%C3%A9");
+    }
+
+    // The server's early exit compare without the server: a millisecond per matching byte, some jitter,
+    // and now and then a request the scheduler held up.
+    fn simulated_request(tag: &[u8], signature: &[u8]) -> (bool, Duration) {
+        let mut rng = thread_rng();
+        let matching = tag
+            .iter()
+            .zip(signature)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let mut micros = 1000 * matching as u64 + rng.gen_range(0..300);
+        if rng.gen_range(0..20) == 0 {
+            micros += 5000;
+        }
+        (tag == signature, Duration::from_micros(micros))
+    }
+
+    #[test]
+    fn test_recover_signature_simulated() {
+        let tag = hmac::<Sha1>(b"YELLOW SUBMARINE", b"foo")[..6].to_vec();
+
+        let signature = recover_signature_with(
+            |signature| Ok(simulated_request(&tag, signature)),
+            tag.len(),
+            &TimingAttackConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(signature, Some(tag.clone()));
+
+        // if nothing ever comes back right the last byte can't be found
+        let signature = recover_signature_with(
+            |signature| Ok((false, simulated_request(&tag, signature).1)),
+            tag.len(),
+            &TimingAttackConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(signature, None);
+    }
+
+    #[test]
+    fn test_recover_signature_bad_config() {
+        let tag = [0x42; 4];
+        for config in [
+            TimingAttackConfig {
+                samples: 0,
+                ..TimingAttackConfig::default()
+            },
+            TimingAttackConfig {
+                max_rounds: 0,
+                ..TimingAttackConfig::default()
+            },
+        ] {
+            let err = recover_signature_with(
+                |signature| Ok(simulated_request(&tag, signature)),
+                tag.len(),
+                &config,
+            )
+            .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    // real sockets and sleeps, so it's slow and at the mercy of whatever else the machine is doing
+    #[test]
+    #[ignore = "timing attack over real sockets, run with --ignored"]
+    fn test_recover_signature() {
+        // a truncated tag to keep the test quick, the attack doesn't care
+        let config = ServerConfig {
+            key: b"YELLOW SUBMARINE".to_vec(),
+            delay_per_byte: Duration::from_millis(5),
+            tag_len: 3,
+        };
+        let expected = hmac::<Sha1>(&config.key, b"foo")[..3].to_vec();
+        let server = HmacServer::start(config).unwrap();
+
+        let signature =
+            recover_signature(server.addr(), "foo", 3, &TimingAttackConfig::default()).unwrap();
+        assert_eq!(signature, Some(expected));
+    }
+}
@@ -0,0 +1,7 @@
+mod attack;
+mod server;
+
+pub use attack::{
+    recover_signature, recover_signature_with, send_signed_request, TimingAttackConfig,
+};
+pub use server::{HmacServer, ServerConfig};
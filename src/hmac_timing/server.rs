@@ -0,0 +1,227 @@
+use crate::hash::{MerkleDamgard, Sha1};
+use crate::hmac::{early_exit_eq_with, hmac};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// how long a connection gets to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub key: Vec<u8>,
+    // how long the comparison sleeps after each matching byte
+    pub delay_per_byte: Duration,
+    // HMAC-SHA1 truncated to this many bytes, 20 for the whole thing
+    pub tag_len: usize,
+}
+
+// The signed file server from cryptopals 31/32 - GET /test?file=...&signature=<hex HMAC-SHA1 of file>
+// answers 200 if the signature is right and 500 if not, checking it with the early exit compare plus a sleep per byte.
+// Requests are handled one at a time so they don't disturb each other's timings.
+pub struct HmacServer {
+    addr: SocketAddr,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl HmacServer {
+    pub fn start(config: ServerConfig) -> io::Result<Self> {
+        if config.tag_len == 0 || config.tag_len > Sha1::digest_size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "tag length {} is not in 1..={}",
+                    config.tag_len,
+                    Sha1::digest_size()
+                ),
+            ));
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let running = Arc::new(AtomicBool::new(true));
+
+        let still_running = running.clone();
+        let handle = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if !still_running.load(Ordering::SeqCst) {
+                    break;
+                }
+                // a client hanging up early isn't the server's problem
+                if let Ok(stream) = stream {
+                    let _ = handle_connection(&config, stream);
+                }
+            }
+        });
+
+        Ok(HmacServer {
+            addr,
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for HmacServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        // wake the accept loop up so it sees it should stop
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn handle_connection(config: &ServerConfig, mut stream: TcpStream) -> io::Result<()> {
+    // connections are handled one at a time, a client that never sends anything mustn't hold up the rest
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // drain the headers
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let status = match parse_query(&request_line) {
+        Some((file, signature)) if signature_is_valid(config, &file, &signature) => "200 OK",
+        Some(_) => "500 Internal Server Error",
+        None => "400 Bad Request",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    )
+}
+
+// file and signature from "GET /test?file=foo&signature=0a1b HTTP/1.1"
+fn parse_query(request_line: &str) -> Option<(String, Vec<u8>)> {
+    let target = request_line.split_whitespace().nth(1)?;
+    let (_, query) = target.split_once('?')?;
+
+    let mut file = None;
+    let mut signature = None;
+    for pair in query.split('&') {
+        match pair.split_once('=') {
+            Some(("file", value)) => file = percent_decode(value),
+            Some(("signature", value)) => signature = hex::decode(value).ok(),
+            _ => {}
+        }
+    }
+    Some((file?, signature?))
+}
+
+// undo the %XX escapes, None if one is malformed or the result isn't utf-8
+fn percent_decode(value: &str) -> Option<String> {
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            decoded.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            decoded.push(b);
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+fn signature_is_valid(config: &ServerConfig, file: &str, signature: &[u8]) -> bool {
+    let tag = hmac::<Sha1>(&config.key, file.as_bytes());
+    insecure_compare(&tag[..config.tag_len], signature, config.delay_per_byte)
+}
+
+// `hmac::early_exit_eq` with a sleep after every matching byte to make the leak easy to hear
+fn insecure_compare(a: &[u8], b: &[u8], delay_per_byte: Duration) -> bool {
+    early_exit_eq_with(a, b, || thread::sleep(delay_per_byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hmac_timing::attack::send_signed_request;
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            parse_query("GET /test?file=foo&signature=0a1b HTTP/1.1\r\n"),
+            Some(("foo".to_string(), vec![0x0a, 0x1b]))
+        );
+        assert_eq!(parse_query("GET /test?file=foo HTTP/1.1\r\n"), None);
+        assert_eq!(
+            parse_query("GET /test?file=foo&signature=xyz HTTP/1.1\r\n"),
+            None
+        );
+        assert_eq!(
+            parse_query("GET /test?file=a%20b%26c&signature=0a HTTP/1.1\r\n"),
+            Some(("a b&c".to_string(), vec![0x0a]))
+        );
+        assert_eq!(
+            parse_query("GET /test?file=a%2&signature=0a HTTP/1.1\r\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_server_checks_signature() {
+        let config = ServerConfig {
+            key: b"YELLOW SUBMARINE".to_vec(),
+            delay_per_byte: Duration::ZERO,
+            tag_len: 20,
+        };
+        let tag = hmac::<Sha1>(&config.key, b"foo");
+        let server = HmacServer::start(config).unwrap();
+
+        let (valid, _) = send_signed_request(server.addr(), "foo", &tag).unwrap();
+        assert!(valid);
+
+        let (valid, _) = send_signed_request(server.addr(), "bar", &tag).unwrap();
+        assert!(!valid);
+
+        // a file name that has to be escaped to get through the query string
+        let file = "a b&signature=00 ?#%";
+        let tag = hmac::<Sha1>(b"YELLOW SUBMARINE", file.as_bytes());
+        let (valid, _) = send_signed_request(server.addr(), file, &tag).unwrap();
+        assert!(valid);
+    }
+
+    #[test]
+    fn test_server_survives_idle_client() {
+        let config = ServerConfig {
+            key: b"YELLOW SUBMARINE".to_vec(),
+            delay_per_byte: Duration::ZERO,
+            tag_len: 20,
+        };
+        let tag = hmac::<Sha1>(&config.key, b"foo");
+        let server = HmacServer::start(config).unwrap();
+
+        // connects and then says nothing, the server gives up on it and moves on
+        let _idle = TcpStream::connect(server.addr()).unwrap();
+        let (valid, _) = send_signed_request(server.addr(), "foo", &tag).unwrap();
+        assert!(valid);
+    }
+
+    #[test]
+    fn test_server_rejects_bad_tag_len() {
+        for tag_len in [0, 21] {
+            let config = ServerConfig {
+                key: b"YELLOW SUBMARINE".to_vec(),
+                delay_per_byte: Duration::ZERO,
+                tag_len,
+            };
+            let err = HmacServer::start(config).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+}
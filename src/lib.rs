@@ -3,8 +3,17 @@ extern crate core;
 
 use crate::scoring::{ChiSquaredScore, Scorer};
 
+pub use error::CryptoError;
+
+pub mod bignum;
 pub mod block;
+pub mod error;
+pub mod hash;
+pub mod hmac;
+pub mod hmac_timing;
+pub mod prng;
 pub mod scoring;
+pub mod timing;
 pub mod xor;
 
 pub fn hex_to_base_64(hex_str: &str) -> String {
@@ -0,0 +1,50 @@
+use std::cell::Cell;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Unix time in seconds - a trait so attacks and the things they attack can share a fake one in tests
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+}
+
+// a clock that only moves when told to
+#[derive(Debug, Default)]
+pub struct ManualClock(Cell<u64>);
+
+impl ManualClock {
+    pub fn new(now: u64) -> Self {
+        ManualClock(Cell::new(now))
+    }
+
+    pub fn advance(&self, secs: u64) {
+        self.0.set(self.0.get() + secs);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.0.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new(1_000_000);
+        clock.advance(40);
+        assert_eq!(clock.now(), 1_000_040);
+    }
+}
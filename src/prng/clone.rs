@@ -0,0 +1,102 @@
+use crate::prng::mt19937::{Mt19937, N};
+
+// after the window the stream needs this many more outputs to confirm a clone
+const CONFIRM_OUTPUTS: usize = 8;
+
+// y ^= y >> shift undone one block of `shift` bits at a time from the top
+fn undo_right_shift(y: u32, shift: u32) -> u32 {
+    let mut x = y;
+    for _i in 0..32 / shift {
+        x = y ^ (x >> shift);
+    }
+    x
+}
+
+// y ^= (y << shift) & mask undone from the bottom
+fn undo_left_shift(y: u32, shift: u32, mask: u32) -> u32 {
+    let mut x = y;
+    for _i in 0..32 / shift {
+        x = y ^ ((x << shift) & mask);
+    }
+    x
+}
+
+pub fn untemper(y: u32) -> u32 {
+    let y = undo_right_shift(y, 18);
+    let y = undo_left_shift(y, 15, 0xefc60000);
+    let y = undo_left_shift(y, 7, 0x9d2c5680);
+    undo_right_shift(y, 11)
+}
+
+// Every output is one tempered state word, so 624 of them in a row untemper to a full state.
+// The clone produces whatever the original produces after these outputs.
+pub fn clone_from_outputs(outputs: &[u32; N]) -> Mt19937 {
+    Mt19937::from_state(outputs.map(untemper))
+}
+
+// As above but for a stream where we don't know which values are MT outputs, e.g. tokens with some other junk first.
+// Where the twist happened doesn't matter - the state words follow a recurrence and any 624 in a row predict the rest -
+// so slide a 624 output window along and take the first one that predicts the outputs after it.
+// Returns where the window starts and a clone positioned after the end of the stream.
+// Only the top bit of the first state word is ever used, so a window one junk output early can still fit - the clone is right either way.
+pub fn clone_from_stream(stream: &[u32]) -> Option<(usize, Mt19937)> {
+    let last_start = stream.len().checked_sub(N + CONFIRM_OUTPUTS)?;
+
+    (0..=last_start).find_map(|start| {
+        let window: &[u32; N] = stream[start..start + N].try_into().unwrap();
+        let mut clone = clone_from_outputs(window);
+
+        stream[start + N..]
+            .iter()
+            .all(|output| clone.next_u32() == *output)
+            .then_some((start, clone))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prng::mt19937::temper;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_untemper() {
+        for _i in 0..1000 {
+            let y: u32 = thread_rng().gen();
+            assert_eq!(untemper(temper(y)), y);
+            assert_eq!(temper(untemper(y)), y);
+        }
+    }
+
+    #[test]
+    fn test_clone_from_outputs() {
+        let mut mt = Mt19937::new(thread_rng().gen());
+        // not lined up with a twist
+        mt.by_ref().take(thread_rng().gen_range(0..1000)).count();
+
+        let outputs: Vec<u32> = mt.by_ref().take(N).collect();
+        let clone = clone_from_outputs(outputs[..].try_into().unwrap());
+
+        assert!(clone.take(2000).eq(mt.take(2000)));
+    }
+
+    #[test]
+    fn test_clone_from_stream() {
+        let mut mt = Mt19937::new(thread_rng().gen());
+        mt.by_ref().take(thread_rng().gen_range(0..1000)).count();
+
+        let junk = thread_rng().gen_range(0..300);
+        let mut stream: Vec<u32> = (0..junk).map(|_| thread_rng().gen()).collect();
+        stream.extend(mt.by_ref().take(700));
+
+        let (start, clone) = clone_from_stream(&stream).unwrap();
+        assert!(start == junk || start + 1 == junk);
+        assert!(clone.take(1000).eq(mt.take(1000)));
+    }
+
+    #[test]
+    fn test_clone_from_stream_too_short() {
+        let stream: Vec<u32> = Mt19937::new(1).take(N + CONFIRM_OUTPUTS - 1).collect();
+        assert!(clone_from_stream(&stream).is_none());
+    }
+}
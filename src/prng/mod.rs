@@ -0,0 +1,13 @@
+mod clock;
+mod clone;
+mod mt19937;
+mod mt19937_64;
+mod seed_recovery;
+mod stream;
+
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use clone::{clone_from_outputs, clone_from_stream, untemper};
+pub use mt19937::Mt19937;
+pub use mt19937_64::Mt19937_64;
+pub use seed_recovery::{recover_timestamp_seed, recover_timestamp_seeds};
+pub use stream::{is_time_seeded_token, recover_mt19937_stream_seed};
@@ -0,0 +1,143 @@
+// 32 bit Mersenne Twister, following the reference mt19937ar.c
+
+pub const N: usize = 624;
+const M: usize = 397;
+const MATRIX_A: u32 = 0x9908b0df;
+const UPPER_MASK: u32 = 0x80000000;
+const LOWER_MASK: u32 = 0x7fffffff;
+
+pub const DEFAULT_SEED: u32 = 5489;
+
+#[derive(Debug, Clone)]
+pub struct Mt19937 {
+    state: [u32; N],
+    index: usize,
+}
+
+impl Mt19937 {
+    pub fn new(seed: u32) -> Self {
+        let mut state = [0u32; N];
+        state[0] = seed;
+        for i in 1..N {
+            state[i] = 1812433253u32
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 30))
+                .wrapping_add(i as u32);
+        }
+        Mt19937 { state, index: N }
+    }
+
+    // a generator that carries on from `state` as if it had just handed out all of it
+    pub fn from_state(state: [u32; N]) -> Self {
+        Mt19937 { state, index: N }
+    }
+
+    // init_by_array
+    pub fn from_array(key: &[u32]) -> Self {
+        assert!(!key.is_empty());
+        let mut mt = Mt19937::new(19650218);
+        let state = &mut mt.state;
+
+        let (mut i, mut j) = (1, 0);
+        for _k in 0..N.max(key.len()) {
+            state[i] = (state[i] ^ (state[i - 1] ^ (state[i - 1] >> 30)).wrapping_mul(1664525))
+                .wrapping_add(key[j])
+                .wrapping_add(j as u32);
+            i += 1;
+            j += 1;
+            if i >= N {
+                state[0] = state[N - 1];
+                i = 1;
+            }
+            if j >= key.len() {
+                j = 0;
+            }
+        }
+        for _k in 0..N - 1 {
+            state[i] = (state[i] ^ (state[i - 1] ^ (state[i - 1] >> 30)).wrapping_mul(1566083941))
+                .wrapping_sub(i as u32);
+            i += 1;
+            if i >= N {
+                state[0] = state[N - 1];
+                i = 1;
+            }
+        }
+        state[0] = 0x80000000;
+
+        mt
+    }
+
+    fn twist(&mut self) {
+        for i in 0..N {
+            let y = (self.state[i] & UPPER_MASK) | (self.state[(i + 1) % N] & LOWER_MASK);
+            let mag = if y & 1 == 0 { 0 } else { MATRIX_A };
+            self.state[i] = self.state[(i + M) % N] ^ (y >> 1) ^ mag;
+        }
+        self.index = 0;
+    }
+
+    // the outputs as a byte stream, each one little endian
+    pub fn bytes(self) -> impl Iterator<Item = u8> {
+        self.flat_map(u32::to_le_bytes)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        if self.index >= N {
+            self.twist();
+        }
+
+        let y = self.state[self.index];
+        self.index += 1;
+        temper(y)
+    }
+}
+
+pub fn temper(mut y: u32) -> u32 {
+    y ^= y >> 11;
+    y ^= (y << 7) & 0x9d2c5680;
+    y ^= (y << 15) & 0xefc60000;
+    y ^ (y >> 18)
+}
+
+impl Default for Mt19937 {
+    fn default() -> Self {
+        Mt19937::new(DEFAULT_SEED)
+    }
+}
+
+impl Iterator for Mt19937 {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        Some(self.next_u32())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_seed() {
+        let mut mt = Mt19937::default();
+        assert_eq!(
+            mt.by_ref().take(5).collect::<Vec<_>>(),
+            [3499211612, 581869302, 3890346734, 3586334585, 545404204]
+        );
+
+        // C++ [rand.predef] - the 10000th output of a default constructed std::mt19937
+        assert_eq!(mt.nth(10000 - 6), Some(4123659995));
+    }
+
+    #[test]
+    fn test_init_by_array() {
+        // mt19937ar.out
+        let mt = Mt19937::from_array(&[0x123, 0x234, 0x345, 0x456]);
+        assert_eq!(
+            mt.take(10).collect::<Vec<_>>(),
+            [
+                1067595299, 955945823, 477289528, 4107218783, 4228976476, 3344332714, 3355579695,
+                227628506, 810200273, 2591290167
+            ]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+// 64 bit Mersenne Twister, following the reference mt19937-64.c
+
+const NN: usize = 312;
+const MM: usize = 156;
+const MATRIX_A: u64 = 0xb5026f5aa96619e9;
+const UPPER_MASK: u64 = 0xffffffff80000000;
+const LOWER_MASK: u64 = 0x7fffffff;
+
+pub const DEFAULT_SEED: u64 = 5489;
+
+#[derive(Debug, Clone)]
+pub struct Mt19937_64 {
+    state: [u64; NN],
+    index: usize,
+}
+
+impl Mt19937_64 {
+    pub fn new(seed: u64) -> Self {
+        let mut state = [0u64; NN];
+        state[0] = seed;
+        for i in 1..NN {
+            state[i] = 6364136223846793005u64
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 62))
+                .wrapping_add(i as u64);
+        }
+        Mt19937_64 { state, index: NN }
+    }
+
+    // init_by_array64
+    pub fn from_array(key: &[u64]) -> Self {
+        assert!(!key.is_empty());
+        let mut mt = Mt19937_64::new(19650218);
+        let state = &mut mt.state;
+
+        let (mut i, mut j) = (1, 0);
+        for _k in 0..NN.max(key.len()) {
+            state[i] = (state[i]
+                ^ (state[i - 1] ^ (state[i - 1] >> 62)).wrapping_mul(3935559000370003845))
+            .wrapping_add(key[j])
+            .wrapping_add(j as u64);
+            i += 1;
+            j += 1;
+            if i >= NN {
+                state[0] = state[NN - 1];
+                i = 1;
+            }
+            if j >= key.len() {
+                j = 0;
+            }
+        }
+        for _k in 0..NN - 1 {
+            state[i] = (state[i]
+                ^ (state[i - 1] ^ (state[i - 1] >> 62)).wrapping_mul(2862933555777941757))
+            .wrapping_sub(i as u64);
+            i += 1;
+            if i >= NN {
+                state[0] = state[NN - 1];
+                i = 1;
+            }
+        }
+        state[0] = 1 << 63;
+
+        mt
+    }
+
+    fn twist(&mut self) {
+        for i in 0..NN {
+            let x = (self.state[i] & UPPER_MASK) | (self.state[(i + 1) % NN] & LOWER_MASK);
+            let mag = if x & 1 == 0 { 0 } else { MATRIX_A };
+            self.state[i] = self.state[(i + MM) % NN] ^ (x >> 1) ^ mag;
+        }
+        self.index = 0;
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        if self.index >= NN {
+            self.twist();
+        }
+
+        let mut x = self.state[self.index];
+        self.index += 1;
+
+        x ^= (x >> 29) & 0x5555555555555555;
+        x ^= (x << 17) & 0x71d67fffeda60000;
+        x ^= (x << 37) & 0xfff7eee000000000;
+        x ^ (x >> 43)
+    }
+}
+
+impl Default for Mt19937_64 {
+    fn default() -> Self {
+        Mt19937_64::new(DEFAULT_SEED)
+    }
+}
+
+impl Iterator for Mt19937_64 {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        Some(self.next_u64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_seed() {
+        // C++ [rand.predef] - the 10000th output of a default constructed std::mt19937_64
+        assert_eq!(
+            Mt19937_64::default().nth(10000 - 1),
+            Some(9981545732273789042)
+        );
+    }
+
+    #[test]
+    fn test_init_by_array() {
+        // mt19937-64.out
+        let mt = Mt19937_64::from_array(&[0x12345, 0x23456, 0x34567, 0x45678]);
+        assert_eq!(
+            mt.take(5).collect::<Vec<_>>(),
+            [
+                7266447313870364031,
+                4946485549665804864,
+                16945909448695747420,
+                16394063075524226720,
+                4873882236456199058
+            ]
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::prng::clock::Clock;
+use crate::prng::Mt19937;
+
+// Find every seed in the last `window` seconds whose generator produced `observed` as its outputs starting at `nth`,
+// after each output went through `mask` - e.g. `|r| r % 1000` when the victim only shows rand() % 1000.
+// A lossy mask lets some wrong seeds through by chance, more observed outputs weeds them out.
+pub fn recover_timestamp_seeds<C: Clock, F: Fn(u32) -> u32>(
+    clock: &C,
+    window: u64,
+    nth: usize,
+    observed: &[u32],
+    mask: F,
+) -> Vec<u32> {
+    let now = clock.now();
+
+    (now.saturating_sub(window)..=now)
+        .rev()
+        .map(|seed| seed as u32)
+        .filter(|&seed| {
+            Mt19937::new(seed)
+                .skip(nth)
+                .zip(observed)
+                .all(|(output, expected)| mask(output) == *expected)
+        })
+        .collect()
+}
+
+// the simple case - the first untouched output of a generator seeded with the time some point in the last `window` seconds
+pub fn recover_timestamp_seed<C: Clock>(clock: &C, window: u64, first_output: u32) -> Option<u32> {
+    recover_timestamp_seeds(clock, window, 0, &[first_output], |r| r)
+        .first()
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prng::clock::ManualClock;
+    use rand::{thread_rng, Rng};
+
+    // cryptopals 22 without the sleeping - seed from the clock, wait a while, hand out an output
+    fn seeded_from_clock(clock: &ManualClock) -> (u32, Mt19937) {
+        clock.advance(thread_rng().gen_range(40..1000));
+        let seed = clock.now() as u32;
+        clock.advance(thread_rng().gen_range(40..1000));
+        (seed, Mt19937::new(seed))
+    }
+
+    #[test]
+    fn test_recover_timestamp_seed() {
+        let clock = ManualClock::new(1_650_000_000);
+        let (seed, mut mt) = seeded_from_clock(&clock);
+
+        assert_eq!(
+            recover_timestamp_seed(&clock, 2000, mt.next_u32()),
+            Some(seed)
+        );
+    }
+
+    #[test]
+    fn test_recover_timestamp_seed_outside_window() {
+        let clock = ManualClock::new(1_650_000_000);
+        let (_seed, mut mt) = seeded_from_clock(&clock);
+        let output = mt.next_u32();
+
+        clock.advance(5000);
+        assert_eq!(recover_timestamp_seed(&clock, 2000, output), None);
+    }
+
+    #[test]
+    fn test_recover_timestamp_seed_nth_output() {
+        let clock = ManualClock::new(1_650_000_000);
+        let (seed, mut mt) = seeded_from_clock(&clock);
+
+        let output = mt.nth(1000).unwrap();
+        assert_eq!(
+            recover_timestamp_seeds(&clock, 2000, 1000, &[output], |r| r),
+            vec![seed]
+        );
+    }
+
+    #[test]
+    fn test_recover_timestamp_seed_masked() {
+        let clock = ManualClock::new(1_650_000_000);
+        let (seed, mt) = seeded_from_clock(&clock);
+
+        // rand() % 1000 - one output matches about 1 in 1000 seeds, four pin it down
+        let observed: Vec<u32> = mt.skip(3).take(4).map(|r| r % 1000).collect();
+
+        let one = recover_timestamp_seeds(&clock, 2000, 3, &observed[..1], |r| r % 1000);
+        assert!(one.contains(&seed));
+
+        let four = recover_timestamp_seeds(&clock, 2000, 3, &observed, |r| r % 1000);
+        assert_eq!(four, vec![seed]);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::prng::clock::Clock;
+use crate::prng::Mt19937;
+
+// Brute force the 16 bit seed of `xor::mt19937_xor` given plain text we know the cipher text ends with.
+// Only the keystream under the known suffix gets checked, whatever came before it can be anything.
+pub fn recover_mt19937_stream_seed(cipher_text: &[u8], known_suffix: &[u8]) -> Option<u16> {
+    if known_suffix.is_empty() || known_suffix.len() > cipher_text.len() {
+        return None;
+    }
+    let offset = cipher_text.len() - known_suffix.len();
+    let keystream: Vec<u8> = cipher_text[offset..]
+        .iter()
+        .zip(known_suffix)
+        .map(|(c, p)| c ^ p)
+        .collect();
+
+    (0..=u16::MAX).find(|&seed| {
+        Mt19937::new(seed as u32)
+            .bytes()
+            .skip(offset)
+            .zip(&keystream)
+            .all(|(k, expected)| k == *expected)
+    })
+}
+
+// Was `token` the start of the byte stream of an MT19937 seeded with the time at some point in the last `window` seconds?
+pub fn is_time_seeded_token<C: Clock>(clock: &C, window: u64, token: &[u8]) -> bool {
+    let now = clock.now();
+
+    !token.is_empty()
+        && (now.saturating_sub(window)..=now).any(|seed| {
+            Mt19937::new(seed as u32)
+                .bytes()
+                .zip(token)
+                .all(|(b, expected)| b == *expected)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prng::clock::ManualClock;
+    use crate::xor::mt19937_xor;
+    use rand::{thread_rng, Rng};
+
+    fn reset_token<C: Clock>(clock: &C) -> Vec<u8> {
+        Mt19937::new(clock.now() as u32).bytes().take(16).collect()
+    }
+
+    #[test]
+    fn test_recover_mt19937_stream_seed() {
+        let mut rng = thread_rng();
+        let seed: u16 = rng.gen();
+
+        let mut plain_text: Vec<u8> = (0..rng.gen_range(5..40)).map(|_| rng.gen()).collect();
+        plain_text.extend(b"AAAAAAAAAAAAAA");
+        let cipher_text = mt19937_xor(&plain_text, seed);
+
+        assert_eq!(
+            recover_mt19937_stream_seed(&cipher_text, b"AAAAAAAAAAAAAA"),
+            Some(seed)
+        );
+    }
+
+    #[test]
+    fn test_recover_mt19937_stream_seed_bad_suffix() {
+        assert_eq!(recover_mt19937_stream_seed(b"short", b"too long"), None);
+        assert_eq!(recover_mt19937_stream_seed(b"short", b""), None);
+    }
+
+    #[test]
+    fn test_is_time_seeded_token() {
+        let clock = ManualClock::new(1_650_000_000);
+        let token = reset_token(&clock);
+        clock.advance(300);
+
+        assert!(is_time_seeded_token(&clock, 600, &token));
+
+        let mut random_token = [0u8; 16];
+        thread_rng().fill(&mut random_token);
+        assert!(!is_time_seeded_token(&clock, 600, &random_token));
+
+        // too long ago
+        clock.advance(3600);
+        assert!(!is_time_seeded_token(&clock, 600, &token));
+    }
+}
@@ -0,0 +1,365 @@
+use std::time::Instant;
+
+// Statistics for timing side channels - sample a closure, throw away what the scheduler did to it,
+// and decide which candidate really is slower.
+
+// Call `f` `runs` times and return how long each call took, in seconds.
+pub fn sample<F: FnMut()>(runs: usize, mut f: F) -> Vec<f64> {
+    (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed().as_secs_f64()
+        })
+        .collect()
+}
+
+// The summaries are None for no samples at all rather than NaN or a panic.
+
+pub fn mean(samples: &[f64]) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    Some(samples.iter().sum::<f64>() / samples.len() as f64)
+}
+
+// sample variance, 0 for a single sample
+pub fn variance(samples: &[f64]) -> Option<f64> {
+    let m = mean(samples)?;
+    if samples.len() < 2 {
+        return Some(0.0);
+    }
+    Some(samples.iter().map(|s| (s - m) * (s - m)).sum::<f64>() / (samples.len() - 1) as f64)
+}
+
+fn sorted(samples: &[f64]) -> Vec<f64> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+// `p` in 0..=1, linearly interpolated between the samples either side
+pub fn percentile(samples: &[f64], p: f64) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let sorted = sorted(samples);
+    let rank = p.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64))
+}
+
+pub fn median(samples: &[f64]) -> Option<f64> {
+    percentile(samples, 0.5)
+}
+
+// mean of what's left after dropping `trim` of the samples from each end, the median if that's everything
+pub fn trimmed_mean(samples: &[f64], trim: f64) -> Option<f64> {
+    let sorted = sorted(samples);
+    let cut = ((sorted.len() as f64 * trim) as usize).min(sorted.len() / 2);
+    let kept = &sorted[cut..sorted.len() - cut];
+    if kept.is_empty() {
+        return median(samples);
+    }
+    mean(kept)
+}
+
+// Tukey's fences - drop anything more than 1.5 interquartile ranges outside the middle half.
+// Timing noise is nearly all one way (something else got the CPU) so this mostly trims the slow end.
+pub fn reject_outliers(samples: &[f64]) -> Vec<f64> {
+    if samples.len() < 4 {
+        return samples.to_vec();
+    }
+    let (Some(q1), Some(q3)) = (percentile(samples, 0.25), percentile(samples, 0.75)) else {
+        return samples.to_vec();
+    };
+    let fence = 1.5 * (q3 - q1);
+    samples
+        .iter()
+        .cloned()
+        .filter(|s| *s >= q1 - fence && *s <= q3 + fence)
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WelchTest {
+    pub t: f64,
+    pub degrees_of_freedom: f64,
+    // chance of a t this big if `a` is really no slower than `b` - one sided
+    pub p_value: f64,
+}
+
+// Welch's t-test that `a` has a bigger mean than `b`, without assuming they're equally noisy.
+// None if either side has no samples.
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> Option<WelchTest> {
+    let (va, vb) = (variance(a)? / a.len() as f64, variance(b)? / b.len() as f64);
+    let diff = mean(a)? - mean(b)?;
+    let se = (va + vb).sqrt();
+
+    // identical constant samples say nothing, constant but different samples say everything
+    if se == 0.0 {
+        let t = if diff > 0.0 {
+            f64::INFINITY
+        } else if diff < 0.0 {
+            f64::NEG_INFINITY
+        } else {
+            0.0
+        };
+        let p_value = if diff > 0.0 {
+            0.0
+        } else if diff < 0.0 {
+            1.0
+        } else {
+            0.5
+        };
+        return Some(WelchTest {
+            t,
+            degrees_of_freedom: (a.len() + b.len()).saturating_sub(2) as f64,
+            p_value,
+        });
+    }
+
+    let t = diff / se;
+    let degrees_of_freedom = (va + vb).powi(2)
+        / (va * va / (a.len() - 1).max(1) as f64 + vb * vb / (b.len() - 1).max(1) as f64);
+
+    Some(WelchTest {
+        t,
+        degrees_of_freedom,
+        p_value: student_t_upper_tail(t, degrees_of_freedom),
+    })
+}
+
+// Compare two candidates at a percentile instead of the mean - a low percentile is the least disturbed
+// by the scheduler. Positive when `a` is slower.
+pub fn percentile_difference(a: &[f64], b: &[f64], p: f64) -> Option<f64> {
+    Some(percentile(a, p)? - percentile(b, p)?)
+}
+
+// P(T > t) for Student's t with `df` degrees of freedom
+pub fn student_t_upper_tail(t: f64, df: f64) -> f64 {
+    let tail = 0.5 * incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t >= 0.0 {
+        tail
+    } else {
+        1.0 - tail
+    }
+}
+
+// regularized incomplete beta I_x(a, b), by continued fraction (Numerical Recipes betacf)
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // the fraction converges quickly on one side of the mean, use the symmetry for the other
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let clamp = |v: f64| if v.abs() < TINY { TINY } else { v };
+
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..300 {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        h *= d * c;
+
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let step = d * c;
+        h *= step;
+        if (step - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+    h
+}
+
+// Lanczos approximation
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |sum, (i, c)| {
+            sum + c / (x + 1.0 + i as f64)
+        });
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ranked<T> {
+    pub candidate: T,
+    // trimmed mean of the samples left after outlier rejection
+    pub time: f64,
+    // how sure we are this candidate is slower than the next one down, 1 - the Welch p value
+    pub confidence: f64,
+}
+
+// Rank candidates slowest first from samples already taken.
+// A candidate with no samples has no time to rank it by and is left out.
+pub fn rank<T>(candidates: Vec<(T, Vec<f64>)>, trim: f64) -> Vec<Ranked<T>> {
+    let mut cleaned: Vec<(T, Vec<f64>, f64)> = candidates
+        .into_iter()
+        .filter_map(|(candidate, samples)| {
+            let kept = reject_outliers(&samples);
+            let time = trimmed_mean(&kept, trim)?;
+            Some((candidate, kept, time))
+        })
+        .collect();
+    cleaned.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    let confidences: Vec<f64> = cleaned
+        .iter()
+        .enumerate()
+        .map(|(i, (_, samples, _))| match cleaned.get(i + 1) {
+            Some((_, next, _)) => welch_t_test(samples, next).map_or(0.0, |t| 1.0 - t.p_value),
+            None => 1.0,
+        })
+        .collect();
+
+    cleaned
+        .into_iter()
+        .zip(confidences)
+        .map(|((candidate, _, time), confidence)| Ranked {
+            candidate,
+            time,
+            confidence,
+        })
+        .collect()
+}
+
+// Time `f` on each candidate `runs` times, interleaving the candidates so slow patches hit them all alike, and rank them.
+pub fn rank_by_timing<T, F: FnMut(&T)>(
+    candidates: Vec<T>,
+    runs: usize,
+    trim: f64,
+    mut f: F,
+) -> Vec<Ranked<T>> {
+    let mut samples = vec![Vec::with_capacity(runs); candidates.len()];
+    for _run in 0..runs {
+        for (candidate, samples) in candidates.iter().zip(samples.iter_mut()) {
+            samples.extend(sample(1, || f(candidate)));
+        }
+    }
+    rank(candidates.into_iter().zip(samples).collect(), trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn test_summaries() {
+        let samples = [100.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, -50.0];
+        assert_eq!(trimmed_mean(&samples, 0.1), Some(2.0));
+        assert_eq!(trimmed_mean(&[1.0, 5.0, 9.0], 0.9), Some(5.0));
+        assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&[4.0, 1.0, 2.0, 3.0]), Some(2.5));
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0, 5.0], 0.25), Some(2.0));
+        assert_eq!(variance(&[1.0, 2.0, 3.0, 4.0]), Some(5.0 / 3.0));
+        assert_eq!(variance(&[1.0]), Some(0.0));
+        assert_eq!(reject_outliers(&samples), vec![2.0; 8]);
+
+        // nothing to summarise
+        assert_eq!(mean(&[]), None);
+        assert_eq!(variance(&[]), None);
+        assert_eq!(percentile(&[], 0.5), None);
+        assert_eq!(trimmed_mean(&[], 0.2), None);
+        assert_eq!(reject_outliers(&[]), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn test_student_t_upper_tail() {
+        // df 1 is the Cauchy distribution
+        assert!(close(student_t_upper_tail(1.0, 1.0), 0.25));
+        assert!(close(student_t_upper_tail(0.0, 7.0), 0.5));
+        // the 97.5% points from a t table
+        assert!(close(student_t_upper_tail(2.228, 10.0), 0.025));
+        assert!(close(student_t_upper_tail(1.96, 1e6), 0.025));
+        assert!(close(student_t_upper_tail(-2.228, 10.0), 0.975));
+    }
+
+    #[test]
+    fn test_welch_t_test() {
+        let a = [5.0, 6.0, 7.0, 6.0, 5.5];
+        let b = [1.0, 2.0, 1.5, 2.5, 1.0, 2.0];
+
+        let test = welch_t_test(&a, &b).unwrap();
+        assert!(test.t > 0.0);
+        assert!(test.p_value < 1e-4);
+        assert!(welch_t_test(&b, &a).unwrap().p_value > 1.0 - 1e-4);
+
+        let same = welch_t_test(&a, &a).unwrap();
+        assert_eq!(same.t, 0.0);
+        assert!(close(same.p_value, 0.5));
+
+        assert_eq!(welch_t_test(&[2.0, 2.0], &[1.0, 1.0]).unwrap().p_value, 0.0);
+        assert_eq!(welch_t_test(&a, &[]), None);
+    }
+
+    #[test]
+    fn test_rank() {
+        let ranked = rank(
+            vec![
+                ('a', vec![1.0, 1.1, 0.9, 1.0, 30.0]),
+                ('b', vec![2.0, 2.1, 1.9, 2.0, 2.05]),
+                ('c', vec![1.0, 1.05, 0.95, 1.0, 0.9]),
+                ('d', vec![]),
+            ],
+            0.0,
+        );
+        assert_eq!(ranked.len(), 3);
+
+        assert_eq!(ranked[0].candidate, 'b');
+        assert!(ranked[0].confidence > 0.999);
+        // 'a' only looked slow because of its outlier
+        assert!(ranked[1].time < 1.1);
+        assert!(ranked[1].confidence < 0.99);
+        assert_eq!(ranked[2].confidence, 1.0);
+    }
+
+    #[test]
+    fn test_rank_by_timing() {
+        // real sleeps, so only the order is checked - gaps this wide survive a busy machine,
+        // the confidences are `test_rank`'s job on samples that don't depend on the clock
+        let ranked = rank_by_timing(vec![0u64, 20, 8], 5, 0.2, |ms| {
+            thread::sleep(Duration::from_millis(*ms))
+        });
+        let order: Vec<u64> = ranked.iter().map(|r| r.candidate).collect();
+        assert_eq!(order, vec![20, 8, 0]);
+
+        assert!(rank_by_timing(vec![1u64, 2], 0, 0.2, |_| {}).is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::prng::Mt19937;
 use crate::scoring::ScoredDecrypt;
 use crate::{hamming_distance, scoring, ChiSquaredScore, Scorer};
 use std::cmp::Ordering;
@@ -19,6 +20,15 @@ pub fn repeating_key_xor(input: &[u8], key: &[u8]) -> Vec<u8> {
         .collect()
 }
 
+// cryptopals 24 - a stream cipher with MT19937 as the keystream, and a seed small enough to brute force
+pub fn mt19937_xor(input: &[u8], seed: u16) -> Vec<u8> {
+    input
+        .iter()
+        .zip(Mt19937::new(seed as u32).bytes())
+        .map(|(b, k)| b ^ k)
+        .collect()
+}
+
 pub fn guess_single_byte_xor(input: &[u8]) -> Vec<ScoredDecrypt> {
     let scorer = scoring::ChiSquaredScorer {};
 
@@ -101,6 +111,97 @@ fn break_repeated_key_xor(input: &[u8]) -> Vec<String> {
     guesses // TODO would be nice to return a score decrypt
 }
 
+// roughly how much of English text is spaces, and other digits and punctuation
+const SPACE_RATE: f32 = 0.15;
+const OTHER_PRINTABLE_RATE: f32 = 0.03;
+
+// The letter chi squared only looks at the letters, so on short columns a key that turns everything into
+// letters, or spaces into punctuation, can win. Add spaces and the other non-letters as two more categories.
+fn column_score(decrypt: &ScoredDecrypt) -> f32 {
+    let len = decrypt.plain_text.len() as f32;
+    let spaces = decrypt.plain_text.chars().filter(|c| *c == ' ').count() as f32;
+    let other = decrypt.score.other_printable as f32 - spaces;
+
+    let category = |observed: f32, rate: f32| {
+        let expected = rate * len;
+        (observed - expected) * (observed - expected) / expected
+    };
+
+    let chi = if decrypt.score.chi.is_nan() {
+        0.0
+    } else {
+        decrypt.score.chi
+    };
+    chi + category(spaces, SPACE_RATE) + category(other, OTHER_PRINTABLE_RATE)
+}
+
+// Best single byte xor key for a column of fixed nonce cipher texts, and how far it beat the runner up:
+// 0 is a dead heat, 1 is no contest.
+// Every key byte is tried, a keystream byte is as likely to be 0xff as anything else.
+fn solve_column(column: &[u8]) -> Option<(u8, f32)> {
+    let scorer = scoring::ChiSquaredScorer {};
+
+    let mut scored: Vec<_> = (0u8..=255u8)
+        .filter_map(|key| {
+            let plain_text = String::from_utf8(single_byte_xor(column, key)).ok()?;
+            let score = scorer.score(&plain_text);
+            Some(ScoredDecrypt {
+                key,
+                score,
+                plain_text,
+            })
+        })
+        .filter(|d| d.score.unprintable == 0)
+        .map(|d| (d.key, column_score(&d)))
+        .collect();
+    scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let (key, best) = *scored.first()?;
+    let confidence = match scored.get(1) {
+        Some((_, second)) if *second > 0.0 => 1.0 - best / second,
+        Some(_) => 0.0,
+        None => 1.0,
+    };
+    Some((key, confidence))
+}
+
+#[derive(Debug)]
+pub struct FixedNonceBreak {
+    pub keystream: Vec<u8>,
+    pub plain_texts: Vec<Vec<u8>>,
+    // per keystream byte, see `solve_column` - 0 where no key gave printable text
+    pub confidence: Vec<f32>,
+}
+
+// Many messages CTR encrypted under the same key and nonce all share one keystream,
+// so column i of the cipher texts is single byte xor with keystream[i] - the repeating key xor break with the key size already known.
+// Cipher texts can be different lengths, each column just uses the cipher texts long enough to have a byte there.
+pub fn break_fixed_nonce_ctr(cipher_texts: &[Vec<u8>]) -> FixedNonceBreak {
+    let max_len = cipher_texts.iter().map(Vec::len).max().unwrap_or(0);
+
+    let (keystream, confidence): (Vec<u8>, Vec<f32>) = (0..max_len)
+        .map(|i| {
+            let column: Vec<u8> = cipher_texts
+                .iter()
+                .filter_map(|c| c.get(i))
+                .cloned()
+                .collect();
+            solve_column(&column).unwrap_or((0, 0.0))
+        })
+        .unzip();
+
+    let plain_texts = cipher_texts
+        .iter()
+        .map(|c| fixed_xor(c, &keystream[..c.len()]))
+        .collect();
+
+    FixedNonceBreak {
+        keystream,
+        plain_texts,
+        confidence,
+    }
+}
+
 fn repeated_xor_block_size_score(input: &[u8], key_size: usize) -> (usize, f32) {
     let b1 = &input[0..key_size];
     let b2 = &input[key_size..key_size * 2];
@@ -149,7 +250,7 @@ mod tests {
         let file = File::open("./challenge-data/4.txt").unwrap();
         let cipher_texts: Vec<_> = io::BufReader::new(file)
             .lines()
-            .filter_map(|l| l.ok())
+            .map_while(Result::ok)
             .filter_map(|l| hex::decode(l).ok())
             .collect();
         let detected = detect_single_byte_xor(&cipher_texts);
@@ -170,6 +271,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mt19937_xor() {
+        let plain_text = b"Burning 'em, if you ain't quick and nimble";
+        let cipher_text = mt19937_xor(plain_text, 0x1234);
+
+        assert_ne!(&cipher_text[..], &plain_text[..]);
+        assert_eq!(mt19937_xor(&cipher_text, 0x1234), plain_text);
+        assert_ne!(mt19937_xor(&cipher_text, 0x1235), plain_text);
+    }
+
     #[test]
     fn test_break_repeated_key_xor() {
         use std::fs::read_to_string;
@@ -181,4 +292,43 @@ mod tests {
         // println!("{}", guesses[0]);
         assert!(guesses[0].starts_with("I'm back and I'm ringin' the bell"));
     }
+
+    #[test]
+    fn test_break_fixed_nonce_ctr() {
+        use crate::block::{aes_128_ctr, aes_128_ecb_decrypt};
+        use rand::Rng;
+        use std::fs::read_to_string;
+
+        // lines of the challenge 7 lyrics, all encrypted with the same nonce
+        let f = read_to_string("./challenge-data/7.txt").unwrap();
+        let f: String = f.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+        let lyrics = aes_128_ecb_decrypt(b"YELLOW SUBMARINE", &base64::decode(f).unwrap()).unwrap();
+        let lines: Vec<&[u8]> = lyrics
+            .split(|b| *b == b'\n')
+            .filter(|l| !l.is_empty())
+            .take(60)
+            .collect();
+
+        let mut key = [0u8; 16];
+        rand::thread_rng().fill(&mut key);
+        let cipher_texts: Vec<_> = lines
+            .iter()
+            .map(|l| aes_128_ctr(&key, 0, l.to_vec()).unwrap())
+            .collect();
+
+        let broken = break_fixed_nonce_ctr(&cipher_texts);
+        let max_len = lines.iter().map(|l| l.len()).max().unwrap();
+        assert_eq!(broken.keystream.len(), max_len);
+        assert_eq!(broken.confidence.len(), max_len);
+
+        let real_keystream = aes_128_ctr(&key, 0, vec![0; max_len]).unwrap();
+
+        // columns with plenty of cipher texts should be right, and confident
+        assert_eq!(broken.keystream[..24], real_keystream[..24]);
+        assert!(broken.confidence[..24].iter().all(|c| *c > 0.0));
+        for (plain_text, line) in broken.plain_texts.iter().zip(&lines) {
+            assert_eq!(plain_text.len(), line.len());
+            assert_eq!(plain_text[..10], line[..10]);
+        }
+    }
 }